@@ -1,16 +1,125 @@
 use ansi_term::Style;
-use chrono::{naive::NaiveDate, Datelike, Local};
+use chrono::{naive::NaiveDate, Datelike, Duration, Local, Locale, Weekday};
 use clap::{App, Arg};
 use itertools::izip;
-use std::{error::Error, str::FromStr};
+use std::{
+    error::Error,
+    io::{self, IsTerminal},
+    str::FromStr,
+};
 
 #[derive(Debug)]
 pub struct Config {
     month: Option<u32>,
     year: i32,
+    last_year: i32,
     today: NaiveDate,
+    months_before: u32,
+    months_after: u32,
+    one_line: bool,
+    week_start: Weekday,
+    layout: Layout,
+    count_weekdays: bool,
 }
 
+impl Config {
+    /// Build a config without going through the command line: show `month`
+    /// of `year`, or the whole year when `month` is `None`, highlighting
+    /// `today`
+    pub fn new(month: Option<u32>, year: i32, today: NaiveDate) -> Self {
+        Config {
+            month,
+            year,
+            last_year: year,
+            today,
+            months_before: 0,
+            months_after: 0,
+            one_line: false,
+            week_start: Weekday::Sun,
+            layout: Layout::new(false),
+            count_weekdays: false,
+        }
+    }
+}
+
+/// How a month grid is rendered: the width of each day cell, the blank
+/// gutter to the right of the grid, whether days show their day of year,
+/// whether each week row starts with its ISO week number, the locale for
+/// month and weekday names, whether weeks run down columns as in ncal, and
+/// how today is emphasized
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Layout {
+    cell_width: usize,
+    gutter: usize,
+    julian: bool,
+    week_numbers: bool,
+    locale: Locale,
+    vertical: bool,
+    highlight: Highlight,
+}
+
+/// The terminal style for today's cell
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Highlight {
+    Reverse,
+    Bold,
+    Underline,
+    None,
+}
+
+impl Highlight {
+    fn style(&self) -> Option<Style> {
+        match self {
+            Highlight::Reverse => Some(Style::new().reverse()),
+            Highlight::Bold => Some(Style::new().bold()),
+            Highlight::Underline => Some(Style::new().underline()),
+            Highlight::None => None,
+        }
+    }
+}
+
+impl Layout {
+    fn new(julian: bool) -> Self {
+        Layout {
+            cell_width: if julian { 3 } else { 2 },
+            gutter: 2,
+            julian,
+            week_numbers: false,
+            locale: Locale::en_US,
+            vertical: false,
+            highlight: Highlight::Reverse,
+        }
+    }
+
+    /// Width of the week-number column to the left of the grid
+    fn prefix_width(&self) -> usize {
+        if self.week_numbers {
+            3
+        } else {
+            0
+        }
+    }
+
+    /// Width of the grid itself: seven cells and the six spaces between
+    /// them, or in the vertical layout a day name and six week columns
+    fn col_width(&self) -> usize {
+        if self.vertical {
+            2 + (self.cell_width + 1) * 6
+        } else {
+            self.cell_width * 7 + 6
+        }
+    }
+
+    /// Width of a whole rendered line, including the week numbers and gutter
+    fn line_width(&self) -> usize {
+        self.prefix_width() + self.col_width() + self.gutter
+    }
+}
+
+/// The most months -A or -B can add
+const MAX_MONTH_COUNT: u32 = 1200;
+
+/// English month names, accepted by -m whatever the output locale
 const MONTH_NAMES: [&str; 12] = [
     "January",
     "February",
@@ -26,8 +135,6 @@ const MONTH_NAMES: [&str; 12] = [
     "December",
 ];
 
-const LINE_WIDTH: usize = 22;
-
 type MyResult<T> = Result<T, Box<dyn Error>>;
 
 pub fn get_args() -> MyResult<Config> {
@@ -55,12 +162,135 @@ pub fn get_args() -> MyResult<Config> {
                 .conflicts_with("month")
                 .help("Show whole current year"),
         )
+        .arg(
+            Arg::with_name("three")
+                .short("3")
+                .long("three")
+                .conflicts_with("show_year")
+                .help("Show previous, current and next month"),
+        )
+        .arg(
+            Arg::with_name("after")
+                .value_name("N")
+                .short("A")
+                .long("after")
+                .takes_value(true)
+                .allow_hyphen_values(true)
+                .conflicts_with("show_year")
+                .help("Also show N months after the month"),
+        )
+        .arg(
+            Arg::with_name("before")
+                .value_name("N")
+                .short("B")
+                .long("before")
+                .takes_value(true)
+                .allow_hyphen_values(true)
+                .conflicts_with("show_year")
+                .help("Also show N months before the month"),
+        )
+        .arg(
+            Arg::with_name("one_line")
+                .long("one-line")
+                .help("Print a one-line summary instead of a grid"),
+        )
+        .arg(
+            Arg::with_name("monday")
+                .short("M")
+                .long("monday")
+                .help("Start weeks on Monday"),
+        )
+        .arg(
+            Arg::with_name("julian")
+                .short("j")
+                .long("julian")
+                .help("Show day-of-year numbers"),
+        )
+        .arg(
+            Arg::with_name("week")
+                .long("week")
+                .help("Show ISO week numbers"),
+        )
+        .arg(
+            Arg::with_name("ncal")
+                .short("v")
+                .long("ncal")
+                .conflicts_with("week")
+                .help("Show weeks as columns, like ncal"),
+        )
+        .arg(
+            Arg::with_name("locale")
+                .value_name("LOCALE")
+                .long("locale")
+                .takes_value(true)
+                .help("Locale for month and day names, e.g. de_DE [default: en_US]"),
+        )
+        .arg(
+            Arg::with_name("from")
+                .value_name("YEAR")
+                .long("from")
+                .requires("to")
+                .conflicts_with_all(&[
+                    "year",
+                    "month",
+                    "show_year",
+                    "three",
+                    "after",
+                    "before",
+                    "one_line",
+                ])
+                .help("First year of a span of whole years"),
+        )
+        .arg(
+            Arg::with_name("to")
+                .value_name("YEAR")
+                .long("to")
+                .requires("from")
+                .help("Last year of a span of whole years"),
+        )
+        .arg(
+            Arg::with_name("highlight_today_style")
+                .value_name("STYLE")
+                .long("highlight-today-style")
+                .takes_value(true)
+                .possible_values(&["reverse", "bold", "underline", "none"])
+                .default_value("reverse")
+                .help("How to emphasize today"),
+        )
+        .arg(
+            Arg::with_name("color")
+                .value_name("WHEN")
+                .long("color")
+                .possible_values(&["auto", "always", "never"])
+                .min_values(0)
+                .require_equals(true)
+                .help("Highlight today WHEN [default: auto, or always without WHEN]"),
+        )
+        .arg(
+            Arg::with_name("count_weekdays")
+                .long("count-weekdays")
+                .conflicts_with_all(&["show_year", "three", "after", "before", "from", "one_line"])
+                .help("Also print how many of each weekday the month has"),
+        )
         .get_matches();
 
     let mut month = matches.value_of("month").map(parse_month).transpose()?;
     let mut year = matches.value_of("year").map(parse_year).transpose()?;
-    let today = Local::today();
-    if matches.is_present("show_year") {
+    let today = Local::now().date_naive();
+    let locale = matches
+        .value_of("locale")
+        .map(|val| Locale::try_from(val).map_err(|_| format!("Invalid locale \"{}\"", val)))
+        .transpose()?
+        .unwrap_or(Locale::en_US);
+    let from = matches.value_of("from").map(parse_year).transpose()?;
+    let to = matches.value_of("to").map(parse_year).transpose()?;
+    if let (Some(from), Some(to)) = (from, to) {
+        if from > to {
+            return Err(format!("--from year {} is after --to year {}", from, to).into());
+        }
+        month = None;
+        year = Some(from);
+    } else if matches.is_present("show_year") {
         month = None;
         year = Some(today.year());
     } else if month.is_none() && year.is_none() {
@@ -68,10 +298,67 @@ pub fn get_args() -> MyResult<Config> {
         year = Some(today.year());
     }
 
+    // -3 is the same as -B 1 -A 1, and widens a smaller window to that
+    if matches.is_present("three") && month.is_none() {
+        return Err(From::from("-3 requires a single month"));
+    }
+    let three = u32::from(matches.is_present("three"));
+    let months_after = matches
+        .value_of("after")
+        .map(parse_month_count)
+        .transpose()?
+        .unwrap_or(0)
+        .max(three);
+    let months_before = matches
+        .value_of("before")
+        .map(parse_month_count)
+        .transpose()?
+        .unwrap_or(0)
+        .max(three);
+    if (matches.is_present("after") || matches.is_present("before")) && month.is_none() {
+        return Err(From::from("-A and -B require a single month"));
+    }
+
+    let count_weekdays = matches.is_present("count_weekdays");
+    if count_weekdays && month.is_none() {
+        return Err(From::from("--count-weekdays requires a single month"));
+    }
+
+    // Like cal, today is only highlighted on a terminal unless asked
+    let color = match matches.value_of("color") {
+        None if matches.is_present("color") => true,
+        None | Some("auto") => io::stdout().is_terminal(),
+        Some(when) => when == "always",
+    };
+
+    let year = year.unwrap_or_else(|| today.year());
     Ok(Config {
         month,
-        year: year.unwrap_or_else(|| today.year()),
-        today: today.naive_local(),
+        year,
+        last_year: to.unwrap_or(year),
+        today,
+        months_before,
+        months_after,
+        one_line: matches.is_present("one_line"),
+        week_start: if matches.is_present("monday") {
+            Weekday::Mon
+        } else {
+            Weekday::Sun
+        },
+        layout: Layout {
+            week_numbers: matches.is_present("week"),
+            locale,
+            vertical: matches.is_present("ncal"),
+            highlight: match matches.value_of("highlight_today_style") {
+                _ if !color => Highlight::None,
+                Some("bold") => Highlight::Bold,
+                Some("underline") => Highlight::Underline,
+                Some("none") => Highlight::None,
+                _ => Highlight::Reverse,
+            },
+            ..Layout::new(matches.is_present("julian"))
+        },
+        count_weekdays,
     })
 }
 
@@ -90,6 +377,20 @@ fn parse_year(year: &str) -> MyResult<i32> {
     })
 }
 
+/// Parse the number of months for -A or -B, at most a century's worth
+fn parse_month_count(val: &str) -> MyResult<u32> {
+    let count: u32 = parse_int(val)
+        .map_err(|_| format!("month count \"{}\" must be a non-negative integer", val))?;
+    if count > MAX_MONTH_COUNT {
+        return Err(format!(
+            "month count \"{}\" not in the range 0 through {}",
+            val, MAX_MONTH_COUNT
+        )
+        .into());
+    }
+    Ok(count)
+}
+
 fn parse_month(month: &str) -> MyResult<u32> {
     match parse_int(month) {
         Ok(m) => match m {
@@ -119,52 +420,118 @@ fn parse_month(month: &str) -> MyResult<u32> {
     }
 }
 
-fn format_month(year: i32, month: u32, print_year: bool, today: NaiveDate) -> Vec<String> {
+/// Render a month as eight lines of equal width: a title, the weekday
+/// names and up to six weeks, padded with blank lines. Today is shown in
+/// reverse video.
+pub fn format_month(year: i32, month: u32, print_year: bool, today: NaiveDate) -> Vec<String> {
+    render_month(
+        year,
+        month,
+        print_year,
+        today,
+        Weekday::Sun,
+        Layout::new(false),
+    )
+}
+
+/// Like [`format_month`], with the week start and layout options of the CLI
+fn render_month(
+    year: i32,
+    month: u32,
+    print_year: bool,
+    today: NaiveDate,
+    week_start: Weekday,
+    layout: Layout,
+) -> Vec<String> {
+    let cell_width = layout.cell_width;
+    let col_width = layout.col_width();
+    let gutter = " ".repeat(layout.gutter);
+    let prefix = " ".repeat(layout.prefix_width());
     let first = NaiveDate::from_ymd_opt(year, month, 1).unwrap();
-    let mut days: Vec<String> = (1..first.weekday().number_from_sunday())
-        .map(|_| "  ".to_string())
-        .collect();
+    let start = week_start.num_days_from_sunday() as usize;
+    let blanks = (first.weekday().num_days_from_sunday() as usize + 7 - start) % 7;
+    let mut days: Vec<String> = (0..blanks).map(|_| " ".repeat(cell_width)).collect();
     let is_today = |day: u32| year == today.year() && month == today.month() && day == today.day();
 
     let last = last_day_in_month(year, month);
     days.extend((first.day()..=last.day()).map(|num| {
-        let fmt = format!("{:>2}", num);
-        if is_today(num) {
-            Style::new().reverse().paint(fmt).to_string()
+        let label = if layout.julian {
+            first.ordinal() + num - 1
         } else {
-            fmt
+            num
+        };
+        let fmt = format!("{:>cell_width$}", label);
+        match layout.highlight.style() {
+            Some(style) if is_today(num) => style.paint(fmt).to_string(),
+            _ => fmt,
         }
     }));
 
-    let month_name = MONTH_NAMES[month as usize - 1];
+    let month_name = month_name(month, layout.locale);
     let mut lines = Vec::with_capacity(8);
     lines.push(format!(
-        "{:^20}  ",
+        "{}{:^col_width$}{}",
+        prefix,
         if print_year {
             format!("{} {}", month_name, year)
         } else {
-            month_name.to_string()
-        }
+            month_name
+        },
+        gutter
     ));
 
-    lines.push("Su Mo Tu We Th Fr Sa  ".to_string());
+    if layout.vertical {
+        for i in 0..7 {
+            let cells: Vec<_> = (0..6)
+                .map(|week| {
+                    days.get(week * 7 + i)
+                        .cloned()
+                        .unwrap_or_else(|| " ".repeat(cell_width))
+                })
+                .collect();
+            lines.push(format!(
+                "{} {}{}",
+                day_name((start + i) % 7, layout.locale),
+                cells.join(" "),
+                gutter
+            ));
+        }
+        return lines;
+    }
+
+    let day_names: Vec<_> = (0..7)
+        .map(|i| format!("{:>cell_width$}", day_name((start + i) % 7, layout.locale)))
+        .collect();
+    lines.push(format!("{}{}{}", prefix, day_names.join(" "), gutter));
 
-    for week in days.chunks(7) {
+    for (row, week) in days.chunks(7).enumerate() {
+        let week_num = if layout.week_numbers {
+            // Label a row by the ISO week of its Monday, even one outside
+            // the month, so Sunday-first rows never repeat a label
+            let row_first = first + Duration::days((row * 7) as i64 - blanks as i64);
+            let to_monday = (7 - row_first.weekday().num_days_from_monday()) % 7;
+            let monday = row_first + Duration::days(to_monday as i64);
+            format!("{:>2} ", monday.iso_week().week())
+        } else {
+            String::new()
+        };
         lines.push(format!(
-            "{:width$}  ",
+            "{}{:col_width$}{}",
+            week_num,
             week.join(" "),
-            width = LINE_WIDTH - 2
+            gutter
         ));
     }
 
     while lines.len() < 8 {
-        lines.push(" ".repeat(LINE_WIDTH));
+        lines.push(" ".repeat(layout.line_width()));
     }
 
     lines
 }
 
-fn last_day_in_month(year: i32, month: u32) -> NaiveDate {
+/// The last day of the given month
+pub fn last_day_in_month(year: i32, month: u32) -> NaiveDate {
     let (y, m) = if month == 12 {
         (year + 1, 1)
     } else {
@@ -177,25 +544,175 @@ fn last_day_in_month(year: i32, month: u32) -> NaiveDate {
         .unwrap()
 }
 
+/// List the (year, month) pairs from `before` months ahead of the given
+/// month through `after` months past it, rolling over year boundaries
+fn month_window(year: i32, month: u32, before: u32, after: u32) -> Vec<(i32, u32)> {
+    let index = year * 12 + month as i32 - 1;
+    (index - before as i32..=index + after as i32)
+        .map(|i| (i.div_euclid(12), i.rem_euclid(12) as u32 + 1))
+        .collect()
+}
+
+/// The full name of a month (1-12) in the given locale
+fn month_name(month: u32, locale: Locale) -> String {
+    NaiveDate::from_ymd_opt(2000, month, 1)
+        .unwrap()
+        .format_localized("%B", locale)
+        .to_string()
+}
+
+/// The two-letter name of a day counted from Sunday (0-6) in the given locale
+fn day_name(day: usize, locale: Locale) -> String {
+    // January 2, 2000 was a Sunday
+    NaiveDate::from_ymd_opt(2000, 1, 2 + day as u32)
+        .unwrap()
+        .format_localized("%a", locale)
+        .to_string()
+        .chars()
+        .take(2)
+        .collect()
+}
+
+/// Summarize the month on one line, with today's date when it falls in
+/// that month, e.g. "April 2021 (Wed 7)"
+fn format_one_line(year: i32, month: u32, today: NaiveDate, locale: Locale) -> String {
+    let summary = format!("{} {}", month_name(month, locale), year);
+    if (today.year(), today.month()) == (year, month) {
+        format!("{} ({})", summary, today.format_localized("%a %-d", locale))
+    } else {
+        summary
+    }
+}
+
+/// How many times each weekday falls in the month, in week order
+/// starting from `week_start`
+fn count_weekdays(year: i32, month: u32, week_start: Weekday) -> Vec<(Weekday, usize)> {
+    let first_day = NaiveDate::from_ymd_opt(year, month, 1).unwrap();
+    let last_day = last_day_in_month(year, month);
+    let mut counts = [0; 7];
+    for day in first_day.iter_days().take_while(|day| *day <= last_day) {
+        counts[day.weekday().num_days_from_monday() as usize] += 1;
+    }
+
+    let mut weekday = week_start;
+    (0..7)
+        .map(|_| {
+            let count = (weekday, counts[weekday.num_days_from_monday() as usize]);
+            weekday = weekday.succ();
+            count
+        })
+        .collect()
+}
+
+/// Report the weekday counts of a month, e.g. "Sun: 4, Mon: 4, ..."
+fn format_weekday_counts(year: i32, month: u32, week_start: Weekday, locale: Locale) -> String {
+    count_weekdays(year, month, week_start)
+        .into_iter()
+        .map(|(weekday, count)| {
+            // January 2, 2000 was a Sunday
+            let name = NaiveDate::from_ymd_opt(2000, 1, 2 + weekday.num_days_from_sunday())
+                .unwrap()
+                .format_localized("%a", locale);
+            format!("{}: {}", name, count)
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 pub fn run(config: Config) -> MyResult<()> {
+    if config.one_line {
+        let month = config.month.unwrap_or_else(|| config.today.month());
+        println!(
+            "{}",
+            format_one_line(config.year, month, config.today, config.layout.locale)
+        );
+        return Ok(());
+    }
+
     match config.month {
+        Some(month) if config.months_before > 0 || config.months_after > 0 => {
+            let months: Vec<_> = month_window(
+                config.year,
+                month,
+                config.months_before,
+                config.months_after,
+            )
+            .into_iter()
+            .map(|(year, month)| {
+                render_month(
+                    year,
+                    month,
+                    true,
+                    config.today,
+                    config.week_start,
+                    config.layout,
+                )
+            })
+            .collect();
+            // Three across, with the last row holding whatever is left
+            for (i, chunk) in months.chunks(3).enumerate() {
+                if i > 0 {
+                    println!();
+                }
+                for row in 0..chunk[0].len() {
+                    let line: String = chunk.iter().map(|lines| lines[row].as_str()).collect();
+                    println!("{}", line);
+                }
+            }
+        }
         Some(month) => {
-            let lines = format_month(config.year, month, true, config.today);
+            let lines = render_month(
+                config.year,
+                month,
+                true,
+                config.today,
+                config.week_start,
+                config.layout,
+            );
             println!("{}", lines.join("\n"));
+            if config.count_weekdays {
+                println!(
+                    "{}",
+                    format_weekday_counts(
+                        config.year,
+                        month,
+                        config.week_start,
+                        config.layout.locale
+                    )
+                );
+            }
         }
         None => {
-            println!("{:>32}", config.year);
-            let months: Vec<_> = (1..=12)
-                .map(|month| format_month(config.year, month, false, config.today))
-                .collect();
+            for year in config.year..=config.last_year {
+                if year > config.year {
+                    println!();
+                }
+                println!(
+                    "{:>width$}",
+                    year,
+                    width = config.layout.line_width() * 3 / 2 - 1
+                );
+                let months: Vec<_> = (1..=12)
+                    .map(|month| {
+                        render_month(
+                            year,
+                            month,
+                            false,
+                            config.today,
+                            config.week_start,
+                            config.layout,
+                        )
+                    })
+                    .collect();
 
-            for (i, chunk) in months.chunks(3).enumerate() {
-                if let [m1, m2, m3] = chunk {
-                    for lines in izip!(m1, m2, m3) {
-                        println!("{}{}{}", lines.0, lines.1, lines.2);
-                    }
-                    if i < 3 {
-                        println!();
+                for (i, chunk) in months.chunks(3).enumerate() {
+                    if let [m1, m2, m3] = chunk {
+                        for lines in izip!(m1, m2, m3) {
+                            println!("{}{}{}", lines.0, lines.1, lines.2);
+                        }
+                        if i < 3 {
+                            println!();
+                        }
                     }
                 }
             }
@@ -207,8 +724,12 @@ pub fn run(config: Config) -> MyResult<()> {
 
 #[cfg(test)]
 mod tests {
-    use super::{format_month, last_day_in_month, parse_int, parse_month, parse_year};
-    use chrono::NaiveDate;
+    use super::{
+        count_weekdays, day_name, format_month, format_one_line, format_weekday_counts,
+        last_day_in_month, month_name, month_window, parse_int, parse_month, parse_month_count,
+        parse_year, render_month, Highlight, Layout,
+    };
+    use chrono::{Locale, NaiveDate, Weekday};
 
     #[test]
     fn test_parse_int() {
@@ -291,7 +812,7 @@ mod tests {
 
     #[test]
     fn test_format_month() {
-        let today = NaiveDate::from_ymd(0, 1, 1);
+        let today = NaiveDate::from_ymd_opt(0, 1, 1).unwrap();
         let leap_february = vec![
             "   February 2020      ",
             "Su Mo Tu We Th Fr Sa  ",
@@ -326,14 +847,329 @@ mod tests {
             "25 26 27 28 29 30     ",
             "                      ",
         ];
-        let today = NaiveDate::from_ymd(2021, 4, 7);
+        let today = NaiveDate::from_ymd_opt(2021, 4, 7).unwrap();
         assert_eq!(format_month(2021, 4, true, today), april_hl);
     }
 
+    #[test]
+    fn test_format_month_monday() {
+        let today = NaiveDate::from_ymd_opt(0, 1, 1).unwrap();
+        let leap_february = vec![
+            "   February 2020      ",
+            "Mo Tu We Th Fr Sa Su  ",
+            "                1  2  ",
+            " 3  4  5  6  7  8  9  ",
+            "10 11 12 13 14 15 16  ",
+            "17 18 19 20 21 22 23  ",
+            "24 25 26 27 28 29     ",
+            "                      ",
+        ];
+        assert_eq!(
+            render_month(2020, 2, true, today, Weekday::Mon, Layout::new(false)),
+            leap_february
+        );
+
+        // A month starting on Monday has no leading blanks
+        let june = vec![
+            "        June          ",
+            "Mo Tu We Th Fr Sa Su  ",
+            " 1  2  3  4  5  6  7  ",
+            " 8  9 10 11 12 13 14  ",
+            "15 16 17 18 19 20 21  ",
+            "22 23 24 25 26 27 28  ",
+            "29 30                 ",
+            "                      ",
+        ];
+        assert_eq!(
+            render_month(2020, 6, false, today, Weekday::Mon, Layout::new(false)),
+            june
+        );
+    }
+
+    #[test]
+    fn test_format_month_julian() {
+        let today = NaiveDate::from_ymd_opt(0, 1, 1).unwrap();
+        let january = vec![
+            "       January 2020          ",
+            " Su  Mo  Tu  We  Th  Fr  Sa  ",
+            "              1   2   3   4  ",
+            "  5   6   7   8   9  10  11  ",
+            " 12  13  14  15  16  17  18  ",
+            " 19  20  21  22  23  24  25  ",
+            " 26  27  28  29  30  31      ",
+            "                             ",
+        ];
+        assert_eq!(
+            render_month(2020, 1, true, today, Weekday::Sun, Layout::new(true)),
+            january
+        );
+
+        // Leap day pushes April 10 to day 101
+        let april = vec![
+            "           April             ",
+            " Su  Mo  Tu  We  Th  Fr  Sa  ",
+            "             92  93  94  95  ",
+            " 96  97  98  99 100 101 102  ",
+            "103 104 105 106 107 108 109  ",
+            "110 111 112 113 114 115 116  ",
+            "117 118 119 120 121          ",
+            "                             ",
+        ];
+        assert_eq!(
+            render_month(2020, 4, false, today, Weekday::Sun, Layout::new(true)),
+            april
+        );
+    }
+
+    #[test]
+    fn test_highlight() {
+        let today = NaiveDate::from_ymd_opt(2021, 4, 7).unwrap();
+        let week = |highlight| {
+            let layout = Layout {
+                highlight,
+                ..Layout::new(false)
+            };
+            render_month(2021, 4, true, today, Weekday::Sun, layout)[3].clone()
+        };
+        assert_eq!(
+            week(Highlight::Reverse),
+            " 4  5  6 \u{1b}[7m 7\u{1b}[0m  8  9 10  "
+        );
+        assert_eq!(
+            week(Highlight::Bold),
+            " 4  5  6 \u{1b}[1m 7\u{1b}[0m  8  9 10  "
+        );
+        assert_eq!(
+            week(Highlight::Underline),
+            " 4  5  6 \u{1b}[4m 7\u{1b}[0m  8  9 10  "
+        );
+        assert_eq!(week(Highlight::None), " 4  5  6  7  8  9 10  ");
+    }
+
+    #[test]
+    fn test_layout() {
+        let layout = Layout::new(false);
+        assert_eq!(layout.col_width(), 20);
+        assert_eq!(layout.line_width(), 22);
+
+        let layout = Layout::new(true);
+        assert_eq!(layout.col_width(), 27);
+        assert_eq!(layout.line_width(), 29);
+
+        // A wider gutter only pads the right of each line
+        let today = NaiveDate::from_ymd_opt(0, 1, 1).unwrap();
+        let layout = Layout {
+            gutter: 4,
+            ..Layout::new(false)
+        };
+        let lines = render_month(2020, 5, false, today, Weekday::Sun, layout);
+        assert_eq!(lines[0], "        May             ");
+        assert_eq!(lines[7], "31                      ");
+    }
+
+    #[test]
+    fn test_format_month_week_numbers() {
+        let today = NaiveDate::from_ymd_opt(0, 1, 1).unwrap();
+        let layout = Layout {
+            week_numbers: true,
+            ..Layout::new(false)
+        };
+
+        // January 1, 2021 falls in the last ISO week of 2020, and Sunday the
+        // 31st starts the row of ISO week 5 rather than repeating week 4
+        let january = vec![
+            "       January 2021      ",
+            "   Su Mo Tu We Th Fr Sa  ",
+            "53                 1  2  ",
+            " 1  3  4  5  6  7  8  9  ",
+            " 2 10 11 12 13 14 15 16  ",
+            " 3 17 18 19 20 21 22 23  ",
+            " 4 24 25 26 27 28 29 30  ",
+            " 5 31                    ",
+        ];
+        assert_eq!(
+            render_month(2021, 1, true, today, Weekday::Sun, layout),
+            january
+        );
+
+        let june = vec![
+            "           June          ",
+            "   Mo Tu We Th Fr Sa Su  ",
+            "23  1  2  3  4  5  6  7  ",
+            "24  8  9 10 11 12 13 14  ",
+            "25 15 16 17 18 19 20 21  ",
+            "26 22 23 24 25 26 27 28  ",
+            "27 29 30                 ",
+            "                         ",
+        ];
+        assert_eq!(
+            render_month(2020, 6, false, today, Weekday::Mon, layout),
+            june
+        );
+    }
+
+    #[test]
+    fn test_format_month_vertical() {
+        let today = NaiveDate::from_ymd_opt(0, 1, 1).unwrap();
+        let layout = Layout {
+            vertical: true,
+            ..Layout::new(false)
+        };
+        let january = vec![
+            "    January 2021      ",
+            "Su     3 10 17 24 31  ",
+            "Mo     4 11 18 25     ",
+            "Tu     5 12 19 26     ",
+            "We     6 13 20 27     ",
+            "Th     7 14 21 28     ",
+            "Fr  1  8 15 22 29     ",
+            "Sa  2  9 16 23 30     ",
+        ];
+        assert_eq!(
+            render_month(2021, 1, true, today, Weekday::Sun, layout),
+            january
+        );
+
+        // A 28-day February starting on Monday fills just four columns
+        let february = vec![
+            "      February        ",
+            "Mo  1  8 15 22        ",
+            "Tu  2  9 16 23        ",
+            "We  3 10 17 24        ",
+            "Th  4 11 18 25        ",
+            "Fr  5 12 19 26        ",
+            "Sa  6 13 20 27        ",
+            "Su  7 14 21 28        ",
+        ];
+        assert_eq!(
+            render_month(2021, 2, false, today, Weekday::Mon, layout),
+            february
+        );
+    }
+
     #[test]
     fn test_last_day_in_month() {
-        assert_eq!(last_day_in_month(2020, 1), NaiveDate::from_ymd(2020, 1, 31));
-        assert_eq!(last_day_in_month(2020, 2), NaiveDate::from_ymd(2020, 2, 29));
-        assert_eq!(last_day_in_month(2020, 4), NaiveDate::from_ymd(2020, 4, 30));
+        assert_eq!(
+            last_day_in_month(2020, 1),
+            NaiveDate::from_ymd_opt(2020, 1, 31).unwrap()
+        );
+        assert_eq!(
+            last_day_in_month(2020, 2),
+            NaiveDate::from_ymd_opt(2020, 2, 29).unwrap()
+        );
+        assert_eq!(
+            last_day_in_month(2020, 4),
+            NaiveDate::from_ymd_opt(2020, 4, 30).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_month_window() {
+        assert_eq!(
+            month_window(2020, 6, 1, 1),
+            [(2020, 5), (2020, 6), (2020, 7)]
+        );
+        assert_eq!(
+            month_window(2020, 1, 1, 1),
+            [(2019, 12), (2020, 1), (2020, 2)]
+        );
+        assert_eq!(
+            month_window(2020, 12, 1, 1),
+            [(2020, 11), (2020, 12), (2021, 1)]
+        );
+        assert_eq!(month_window(2020, 3, 0, 0), [(2020, 3)]);
+
+        // Wider windows roll over in either direction
+        assert_eq!(
+            month_window(2020, 12, 1, 2),
+            [(2020, 11), (2020, 12), (2021, 1), (2021, 2)]
+        );
+        assert_eq!(
+            month_window(2021, 1, 2, 0),
+            [(2020, 11), (2020, 12), (2021, 1)]
+        );
+        assert_eq!(month_window(2020, 12, 0, 13).last(), Some(&(2022, 1)));
+    }
+
+    #[test]
+    fn test_parse_month_count() {
+        assert_eq!(parse_month_count("0").unwrap(), 0);
+        assert_eq!(parse_month_count("2").unwrap(), 2);
+        for bad in ["-1", "x", "1.5"] {
+            let res = parse_month_count(bad);
+            assert!(res.is_err());
+            assert_eq!(
+                res.unwrap_err().to_string(),
+                format!("month count \"{}\" must be a non-negative integer", bad)
+            );
+        }
+
+        // Large counts are refused rather than overflowing the window
+        assert_eq!(parse_month_count("1200").unwrap(), 1200);
+        for bad in ["1201", "2147483647", "3000000000"] {
+            let res = parse_month_count(bad);
+            assert!(res.is_err());
+            assert_eq!(
+                res.unwrap_err().to_string(),
+                format!("month count \"{}\" not in the range 0 through 1200", bad)
+            );
+        }
+    }
+
+    #[test]
+    fn test_format_one_line() {
+        let today = NaiveDate::from_ymd_opt(2021, 4, 7).unwrap();
+        assert_eq!(
+            format_one_line(2021, 4, today, Locale::en_US),
+            "April 2021 (Wed 7)"
+        );
+        // Today is only shown for its own month
+        assert_eq!(
+            format_one_line(2020, 12, today, Locale::en_US),
+            "December 2020"
+        );
+        assert_eq!(format_one_line(2020, 4, today, Locale::en_US), "April 2020");
+        assert_eq!(
+            format_one_line(2021, 4, today, Locale::de_DE),
+            "April 2021 (Mi 7)"
+        );
+    }
+
+    #[test]
+    fn test_count_weekdays() {
+        // February 2020 had 29 days starting on a Saturday
+        let counts = count_weekdays(2020, 2, Weekday::Sun);
+        assert_eq!(
+            counts,
+            [
+                (Weekday::Sun, 4),
+                (Weekday::Mon, 4),
+                (Weekday::Tue, 4),
+                (Weekday::Wed, 4),
+                (Weekday::Thu, 4),
+                (Weekday::Fri, 4),
+                (Weekday::Sat, 5),
+            ]
+        );
+        assert_eq!(count_weekdays(2021, 2, Weekday::Mon)[0], (Weekday::Mon, 4));
+        assert_eq!(
+            format_weekday_counts(2020, 2, Weekday::Mon, Locale::en_US),
+            "Mon: 4, Tue: 4, Wed: 4, Thu: 4, Fri: 4, Sat: 5, Sun: 4"
+        );
+        assert_eq!(
+            format_weekday_counts(2020, 12, Weekday::Sun, Locale::en_US),
+            "Sun: 4, Mon: 4, Tue: 5, Wed: 5, Thu: 5, Fri: 4, Sat: 4"
+        );
+    }
+
+    #[test]
+    fn test_month_and_day_names() {
+        assert_eq!(month_name(2, Locale::en_US), "February");
+        assert_eq!(month_name(1, Locale::de_DE), "Januar");
+        assert_eq!(month_name(3, Locale::fr_FR), "mars");
+        assert_eq!(day_name(0, Locale::en_US), "Su");
+        assert_eq!(day_name(6, Locale::en_US), "Sa");
+        assert_eq!(day_name(1, Locale::de_DE), "Mo");
+        assert_eq!(day_name(3, Locale::de_DE), "Mi");
     }
 }
@@ -12,7 +12,7 @@ pub struct Config {
 
 const MONTH_NAMES: [&str; 12] = [
     "January",
-    "Fabruary",
+    "February",
     "March",
     "April",
     "May",
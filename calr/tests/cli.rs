@@ -1,4 +1,5 @@
 use assert_cmd::Command;
+use chrono::Datelike;
 use predicates::prelude::*;
 use std::{error::Error, fs};
 
@@ -43,7 +44,7 @@ fn dies_invalid_year() -> TestResult {
 #[test]
 fn dies_month_0() -> TestResult {
     Command::cargo_bin(PRG)?
-        .args(&["-m", "0"])
+        .args(["-m", "0"])
         .assert()
         .failure()
         .stderr("month \"0\" not in the range 1 through 12\n");
@@ -54,7 +55,7 @@ fn dies_month_0() -> TestResult {
 #[test]
 fn dies_month_13() -> TestResult {
     Command::cargo_bin(PRG)?
-        .args(&["-m", "13"])
+        .args(["-m", "13"])
         .assert()
         .failure()
         .stderr("month \"13\" not in the range 1 through 12\n");
@@ -65,7 +66,7 @@ fn dies_month_13() -> TestResult {
 #[test]
 fn dies_invalid_month() -> TestResult {
     Command::cargo_bin(PRG)?
-        .args(&["-m", "foo"])
+        .args(["-m", "foo"])
         .assert()
         .failure()
         .stderr("Invalid month \"foo\"\n");
@@ -77,7 +78,7 @@ fn dies_invalid_month() -> TestResult {
 fn dies_y_and_month() -> TestResult {
     let expected = "The argument '-m <MONTH>' cannot be used with '--year'";
     Command::cargo_bin(PRG)?
-        .args(&["-m", "1", "-y"])
+        .args(["-m", "1", "-y"])
         .assert()
         .failure()
         .stderr(predicate::str::contains(expected));
@@ -89,7 +90,7 @@ fn dies_y_and_month() -> TestResult {
 fn dies_y_and_year() -> TestResult {
     let expected = "The argument '<YEAR>' cannot be used with '--year'";
     Command::cargo_bin(PRG)?
-        .args(&["-y", "2000"])
+        .args(["-y", "2000"])
         .assert()
         .failure()
         .stderr(predicate::str::contains(expected));
@@ -116,7 +117,7 @@ fn month_num() -> TestResult {
 
     for (num, month) in expected {
         Command::cargo_bin(PRG)?
-            .args(&["-m", num])
+            .args(["-m", num])
             .assert()
             .success()
             .stdout(predicates::str::contains(month.to_string()));
@@ -143,7 +144,7 @@ fn partial_month() -> TestResult {
 
     for (arg, month) in expected {
         Command::cargo_bin(PRG)?
-            .args(&["-m", arg])
+            .args(["-m", arg])
             .assert()
             .success()
             .stdout(predicates::str::contains(month.to_string()));
@@ -180,6 +181,32 @@ fn test_2_2020_leap_year() -> TestResult {
     run(&["-m", "2", "2020"], "tests/expected/2-2020.txt")
 }
 
+// --------------------------------------------------
+#[test]
+fn count_weekdays() -> TestResult {
+    let expected = fs::read_to_string("tests/expected/2-2020.txt")?;
+    Command::cargo_bin(PRG)?
+        .args(["--count-weekdays", "-m", "2", "2020"])
+        .assert()
+        .success()
+        .stdout(format!(
+            "{}Sun: 4, Mon: 4, Tue: 4, Wed: 4, Thu: 4, Fri: 4, Sat: 5\n",
+            expected
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_count_weekdays_without_month() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--count-weekdays", "2020"])
+        .assert()
+        .failure()
+        .stderr("--count-weekdays requires a single month\n");
+    Ok(())
+}
+
 // --------------------------------------------------
 #[test]
 fn test_4_2020() -> TestResult {
@@ -192,12 +219,125 @@ fn test_april_2020() -> TestResult {
     run(&["2020", "-m", "april"], "tests/expected/4-2020.txt")
 }
 
+// --------------------------------------------------
+#[test]
+fn test_5_2020() -> TestResult {
+    run(&["-m", "5", "2020"], "tests/expected/5-2020.txt")
+}
+
+// --------------------------------------------------
+#[test]
+fn three_months() -> TestResult {
+    run(&["-3", "-m", "1", "2020"], "tests/expected/3-1-2020.txt")
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_three_without_month() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["-3", "2020"])
+        .assert()
+        .failure()
+        .stderr("-3 requires a single month\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_three_and_y() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["-3", "-y"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn month_window() -> TestResult {
+    run(
+        &["-A", "2", "-B", "1", "-m", "6", "2020"],
+        "tests/expected/A2-B1-6-2020.txt",
+    )?;
+    // -3 is one month on either side
+    run(
+        &["-A", "1", "-B", "1", "-m", "1", "2020"],
+        "tests/expected/3-1-2020.txt",
+    )?;
+    run(
+        &["-3", "-B", "0", "-m", "1", "2020"],
+        "tests/expected/3-1-2020.txt",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_month_window() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["-A", "-1", "-m", "6", "2020"])
+        .assert()
+        .failure()
+        .stderr("month count \"-1\" must be a non-negative integer\n");
+    Command::cargo_bin(PRG)?
+        .args(["-A", "2147483647", "-m", "6", "2020"])
+        .assert()
+        .failure()
+        .stderr("month count \"2147483647\" not in the range 0 through 1200\n");
+    Command::cargo_bin(PRG)?
+        .args(["-B", "1", "2020"])
+        .assert()
+        .failure()
+        .stderr("-A and -B require a single month\n");
+    Ok(())
+}
+
 // --------------------------------------------------
 #[test]
 fn test_2020() -> TestResult {
     run(&["2020"], "tests/expected/2020.txt")
 }
 
+// --------------------------------------------------
+#[test]
+fn locale_de() -> TestResult {
+    run(
+        &["--locale", "de_DE", "-m", "1", "2021"],
+        "tests/expected/1-2021.de_DE.txt",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_locale() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--locale", "xx_YY"])
+        .assert()
+        .failure()
+        .stderr("Invalid locale \"xx_YY\"\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn year_span() -> TestResult {
+    run(
+        &["--from", "2020", "--to", "2021"],
+        "tests/expected/2020-2021.txt",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_from_after_to() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--from", "2022", "--to", "2020"])
+        .assert()
+        .failure()
+        .stderr("--from year 2022 is after --to year 2020\n");
+    Ok(())
+}
+
 // --------------------------------------------------
 #[test]
 fn year() -> TestResult {
@@ -205,5 +345,37 @@ fn year() -> TestResult {
     let stdout = String::from_utf8(cmd.get_output().stdout.clone())?;
     let lines: Vec<&str> = stdout.split("\n").collect();
     assert_eq!(lines.len(), 37);
+
+    // The whole-year grid is the same as naming the current year
+    let this_year = chrono::Local::now().year().to_string();
+    assert_eq!(lines[0].trim(), this_year);
+    let explicit = Command::cargo_bin(PRG)?.arg(&this_year).assert().success();
+    assert_eq!(cmd.get_output().stdout, explicit.get_output().stdout);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn highlight_only_on_terminal() -> TestResult {
+    // The current month has today in it, but piped output gets no escapes
+    for args in [&[][..], &["--color=auto"], &["--color=never"]] {
+        Command::cargo_bin(PRG)?
+            .args(args)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("\u{1b}").not());
+    }
+    for args in [&["--color"][..], &["--color=always"]] {
+        Command::cargo_bin(PRG)?
+            .args(args)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("\u{1b}[7m"));
+    }
+    Command::cargo_bin(PRG)?
+        .args(["--color=always", "--highlight-today-style", "none"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\u{1b}").not());
     Ok(())
 }
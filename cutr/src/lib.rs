@@ -13,6 +13,12 @@ use std::{
 type MyResult<T> = Result<T, Box<dyn Error>>;
 type PositionList = Vec<Range<usize>>;
 
+/// An earlier solution, built with the tests so that its own keep running
+#[cfg(test)]
+#[allow(dead_code)]
+#[path = "lib_mysol1.rs"]
+mod mysol1;
+
 #[derive(Debug)]
 pub enum Extract {
     Fields(PositionList),
@@ -24,7 +30,11 @@ pub enum Extract {
 pub struct Config {
     files: Vec<String>,
     delimiter: u8,
+    output_delimiter: Option<String>,
     extract: Extract,
+    complement: bool,
+    only_delimited: bool,
+    regex_delim: Option<Regex>,
 }
 
 pub fn get_args() -> MyResult<Config> {
@@ -45,6 +55,7 @@ pub fn get_args() -> MyResult<Config> {
                 .short("b")
                 .long("bytes")
                 .takes_value(true)
+                .allow_hyphen_values(true)
                 .conflicts_with_all(&["chars", "fields"])
                 .help("Selected bytes"),
         )
@@ -54,6 +65,7 @@ pub fn get_args() -> MyResult<Config> {
                 .short("c")
                 .long("chars")
                 .takes_value(true)
+                .allow_hyphen_values(true)
                 .conflicts_with_all(&["bytes", "fields"])
                 .help("Selected characters"),
         )
@@ -72,12 +84,61 @@ pub fn get_args() -> MyResult<Config> {
                 .short("f")
                 .long("fields")
                 .takes_value(true)
+                .allow_hyphen_values(true)
                 .conflicts_with_all(&["bytes", "chars"])
                 .help("Selected fields"),
         )
+        .arg(
+            Arg::with_name("output_delimiter")
+                .value_name("STRING")
+                .long("output-delimiter")
+                .takes_value(true)
+                .help("Output field delimiter, may use \\n, \\t or \\\\ escapes"),
+        )
+        .arg(
+            Arg::with_name("complement")
+                .long("complement")
+                .takes_value(false)
+                .help("Select all but the given positions"),
+        )
+        .arg(
+            Arg::with_name("only_delimited")
+                .short("s")
+                .long("only-delimited")
+                .takes_value(false)
+                .help("Skip lines without the delimiter when selecting fields"),
+        )
+        .arg(
+            Arg::with_name("regex_delim")
+                .value_name("PATTERN")
+                .long("regex-delim")
+                .takes_value(true)
+                .help("Split fields on a regular expression instead of --delim"),
+        )
+        .arg(
+            Arg::with_name("whitespace")
+                .short("w")
+                .long("whitespace")
+                .takes_value(false)
+                .conflicts_with("regex_delim")
+                .help("Split fields on runs of whitespace"),
+        )
+        .arg(
+            Arg::with_name("csv")
+                .long("csv")
+                .takes_value(false)
+                .requires("fields")
+                .conflicts_with_all(&["regex_delim", "whitespace"])
+                .help("Select fields of comma-separated values [default --delim: ,]"),
+        )
         .get_matches();
 
-    let delimiter = matches.value_of("delimiter").unwrap();
+    // Fields are always read as CSV, so quoted delimiters stay in their
+    // field; --csv only changes the default delimiter to a comma
+    let delimiter = match matches.occurrences_of("delimiter") {
+        0 if matches.is_present("csv") => ",",
+        _ => matches.value_of("delimiter").unwrap(),
+    };
     let delim_bytes = delimiter.as_bytes();
     if delim_bytes.len() != 1 {
         return Err(From::from(format!(
@@ -86,6 +147,16 @@ pub fn get_args() -> MyResult<Config> {
         )));
     }
 
+    let regex_delim = if matches.is_present("whitespace") {
+        Some(r"\s+")
+    } else {
+        matches.value_of("regex_delim")
+    }
+    .map(|pattern| {
+        Regex::new(pattern).map_err(|_| format!("Invalid --regex-delim \"{}\"", pattern))
+    })
+    .transpose()?;
+
     let fields = matches.value_of("fields").map(parse_pos).transpose()?;
     let bytes = matches.value_of("bytes").map(parse_pos).transpose()?;
     let chars = matches.value_of("chars").map(parse_pos).transpose()?;
@@ -103,19 +174,58 @@ pub fn get_args() -> MyResult<Config> {
     Ok(Config {
         files: matches.values_of_lossy("files").unwrap(),
         delimiter: *delim_bytes.first().unwrap(),
+        output_delimiter: matches.value_of("output_delimiter").map(unescape),
         extract,
+        complement: matches.is_present("complement"),
+        only_delimited: matches.is_present("only_delimited"),
+        regex_delim,
     })
 }
 
+/// Expand the escapes `\n`, `\t`, `\r`, `\0` and `\\`; any other
+/// backslash is kept as is
+fn unescape(val: &str) -> String {
+    let mut result = String::with_capacity(val.len());
+    let mut chars = val.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some('r') => result.push('\r'),
+            Some('0') => result.push('\0'),
+            Some('\\') => result.push('\\'),
+            Some(other) => {
+                result.push('\\');
+                result.push(other);
+            }
+            None => result.push('\\'),
+        }
+    }
+    result
+}
+
+/// Parse a list of positions such as "1,3-5". A range missing its start
+/// begins at the first position and one missing its end runs to the end
+/// of the line, which is represented by an end of `usize::MAX`.
 fn parse_pos(range: &str) -> MyResult<PositionList> {
-    let range_re = Regex::new(r"^(\d+)-(\d+)$").unwrap();
+    let range_re = Regex::new(r"^(\d+)?-(\d+)?$").unwrap();
     range
         .split(',')
         .map(|val| {
             parse_index(val).map(|n| n..n + 1).or_else(|e| {
                 range_re.captures(val).ok_or(e).and_then(|captures| {
-                    let n1 = parse_index(&captures[1])?;
-                    let n2 = parse_index(&captures[2])?;
+                    let start = captures.get(1).map(|m| parse_index(m.as_str()));
+                    let end = captures.get(2).map(|m| parse_index(m.as_str()));
+                    let (n1, n2) = match (start, end) {
+                        (None, None) => return Err(format!("illegal list value: \"{}\"", val)),
+                        (Some(n1), None) => return Ok(n1?..usize::MAX),
+                        (None, Some(n2)) => return Ok(0..n2? + 1),
+                        (Some(n1), Some(n2)) => (n1?, n2?),
+                    };
                     if n1 >= n2 {
                         return Err(format!(
                             "First number in range  ({}) \
@@ -134,15 +244,14 @@ fn parse_pos(range: &str) -> MyResult<PositionList> {
 
 fn parse_index(input: &str) -> Result<usize, String> {
     let value_error = || format!("illegal list value: \"{}\"", input);
-    input
-        .starts_with('+')
-        .then(|| Err(value_error()))
-        .unwrap_or_else(|| {
-            input
-                .parse::<NonZeroUsize>()
-                .map(|n| usize::from(n) - 1)
-                .map_err(|_| value_error())
-        })
+    if input.starts_with('+') {
+        Err(value_error())
+    } else {
+        input
+            .parse::<NonZeroUsize>()
+            .map(|n| usize::from(n) - 1)
+            .map_err(|_| value_error())
+    }
 }
 
 fn open(filename: &str) -> MyResult<Box<dyn BufRead>> {
@@ -159,42 +268,99 @@ pub fn run(config: Config) -> MyResult<()> {
             Ok(file) => match &config.extract {
                 Chars(char_pos) => {
                     for line in file.lines() {
-                        println!("{}", extract_chars(&line?, char_pos));
+                        let line = line?;
+                        let pos = select(char_pos, line.chars().count(), config.complement);
+                        println!("{}", extract_chars(&line, &pos));
                     }
                 }
                 Bytes(byte_pos) => {
                     for line in file.lines() {
-                        println!("{}", extract_bytes(&line?, byte_pos));
+                        let line = line?;
+                        let pos = select(byte_pos, line.len(), config.complement);
+                        println!("{}", extract_bytes(&line, &pos));
+                    }
+                }
+                Fields(field_pos) if config.regex_delim.is_some() => {
+                    let re = config.regex_delim.as_ref().unwrap();
+                    let delimiter = (config.delimiter as char).to_string();
+                    let output_delimiter = config.output_delimiter.as_ref().unwrap_or(&delimiter);
+                    for line in file.lines() {
+                        let line = line?;
+                        let record = StringRecord::from(re.split(&line).collect::<Vec<_>>());
+                        if let Some(fields) = record_fields(&record, field_pos, &config) {
+                            println!("{}", fields.join(output_delimiter));
+                        }
                     }
                 }
                 Fields(field_pos) => {
                     let mut reader = ReaderBuilder::new()
                         .delimiter(config.delimiter)
                         .has_headers(false)
+                        .flexible(true)
                         .from_reader(file);
 
-                    let mut wtr = WriterBuilder::new()
-                        .delimiter(config.delimiter)
-                        .from_writer(io::stdout());
-
-                    for record in reader.records() {
-                        let record = record?;
-                        wtr.write_record(extract_fields(&record, field_pos))?;
+                    match &config.output_delimiter {
+                        Some(output_delimiter) => {
+                            for record in reader.records() {
+                                let record = record?;
+                                if let Some(fields) = record_fields(&record, field_pos, &config) {
+                                    println!("{}", fields.join(output_delimiter));
+                                }
+                            }
+                        }
+                        None => {
+                            let mut wtr = WriterBuilder::new()
+                                .delimiter(config.delimiter)
+                                .from_writer(io::stdout());
+
+                            for record in reader.records() {
+                                let record = record?;
+                                match record_fields(&record, field_pos, &config) {
+                                    // The writer would quote an empty record as ""
+                                    Some(fields) if fields.is_empty() => {
+                                        wtr.flush()?;
+                                        println!();
+                                    }
+                                    Some(fields) => wtr.write_record(fields)?,
+                                    None => {}
+                                }
+                            }
+                        }
                     }
                 }
-                _ => unreachable!(),
             },
         }
     }
     Ok(())
 }
 
+/// The positions to extract from a line of `len` bytes, chars or fields:
+/// the given ones, or with `complement` every position they leave out
+fn select(pos: &[Range<usize>], len: usize, complement: bool) -> PositionList {
+    if !complement {
+        return pos.to_vec();
+    }
+    let mut selected = vec![false; len];
+    for range in pos {
+        selected[clamp(range, len)].fill(true);
+    }
+    (0..len)
+        .filter(|&i| !selected[i])
+        .map(|i| i..i + 1)
+        .collect()
+}
+
+/// Limit a range to positions that exist in something of length `len`
+fn clamp(range: &Range<usize>, len: usize) -> Range<usize> {
+    let end = range.end.min(len);
+    range.start.min(end)..end
+}
+
 fn extract_chars(line: &str, char_pos: &[Range<usize>]) -> String {
     let chars: Vec<_> = line.chars().collect();
     char_pos
         .iter()
-        .cloned()
-        .flat_map(|range| range.filter_map(|i| chars.get(i)))
+        .flat_map(|range| clamp(range, chars.len()).map(|i| chars[i]))
         .collect()
 }
 
@@ -202,24 +368,37 @@ fn extract_bytes(line: &str, byte_pos: &[Range<usize>]) -> String {
     let bytes = line.as_bytes();
     let selected: Vec<_> = byte_pos
         .iter()
-        .cloned()
-        .flat_map(|range| range.filter_map(|i| bytes.get(i)).copied())
+        .flat_map(|range| clamp(range, bytes.len()).map(|i| bytes[i]))
         .collect();
 
     String::from_utf8_lossy(&selected).into_owned()
 }
 
+/// The fields to print for a record, or None to skip it. Like cut, a
+/// line without the delimiter is printed whole unless --only-delimited.
+fn record_fields<'a>(
+    record: &'a StringRecord,
+    field_pos: &[Range<usize>],
+    config: &Config,
+) -> Option<Vec<&'a str>> {
+    if record.len() < 2 {
+        return (!config.only_delimited).then(|| record.iter().collect());
+    }
+    let pos = select(field_pos, record.len(), config.complement);
+    Some(extract_fields(record, &pos))
+}
+
 fn extract_fields<'a>(record: &'a StringRecord, field_pos: &[Range<usize>]) -> Vec<&'a str> {
     field_pos
         .iter()
-        .cloned()
-        .flat_map(|range| range.filter_map(|i| record.get(i)))
+        .flat_map(|range| clamp(range, record.len()).filter_map(|i| record.get(i)))
         .collect()
 }
 
 #[cfg(test)]
+#[allow(clippy::single_range_in_vec_init)]
 mod unit_tests {
-    use super::{extract_bytes, extract_chars, extract_fields, parse_pos};
+    use super::{clamp, extract_bytes, extract_chars, extract_fields, parse_pos, select, unescape};
     use csv::StringRecord;
 
     #[test]
@@ -276,7 +455,10 @@ mod unit_tests {
         let res = parse_pos("1,");
         assert!(res.is_err());
 
-        let res = parse_pos("1-");
+        let res = parse_pos("--1");
+        assert!(res.is_err());
+
+        let res = parse_pos("1--");
         assert!(res.is_err());
 
         let res = parse_pos("1-1-1");
@@ -332,6 +514,23 @@ mod unit_tests {
         let res = parse_pos("15,19-20");
         assert!(res.is_ok());
         assert_eq!(res.unwrap(), vec![14..15, 18..20]);
+
+        // Open-ended ranges run from the start or to the end
+        let res = parse_pos("3-");
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), vec![2..usize::MAX]);
+
+        let res = parse_pos("-5");
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), vec![0..5]);
+
+        let res = parse_pos("1-3,5-");
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), vec![0..3, 4..usize::MAX]);
+
+        let res = parse_pos("-0");
+        assert!(res.is_err());
+        assert_eq!(res.unwrap_err().to_string(), "illegal list value: \"0\"");
     }
 
     #[test]
@@ -342,6 +541,7 @@ mod unit_tests {
         assert_eq!(extract_chars("ábc", &[0..3]), "ábc".to_string());
         assert_eq!(extract_chars("ábc", &[2..3, 1..2]), "cb".to_string());
         assert_eq!(extract_chars("ábc", &[0..1, 1..2, 4..5]), "áb".to_string());
+        assert_eq!(extract_chars("ábc", &[1..usize::MAX]), "bc".to_string());
     }
 
     #[test]
@@ -352,6 +552,7 @@ mod unit_tests {
         assert_eq!(extract_bytes("ábc", &[0..4]), "ábc".to_string());
         assert_eq!(extract_bytes("ábc", &[3..4, 2..3]), "cb".to_string());
         assert_eq!(extract_bytes("ábc", &[0..2, 5..6]), "á".to_string());
+        assert_eq!(extract_bytes("ábc", &[2..usize::MAX]), "bc".to_string());
     }
 
     #[test]
@@ -362,5 +563,40 @@ mod unit_tests {
         assert_eq!(extract_fields(&rec, &[0..1, 2..3]), &["Captain", "12345"]);
         assert_eq!(extract_fields(&rec, &[0..1, 3..4]), &["Captain"]);
         assert_eq!(extract_fields(&rec, &[1..2, 0..1]), &["Sham", "Captain"]);
+        assert_eq!(extract_fields(&rec, &[1..usize::MAX]), &["Sham", "12345"]);
+    }
+
+    #[test]
+    fn test_clamp() {
+        assert_eq!(clamp(&(1..3), 5), 1..3);
+        assert_eq!(clamp(&(1..usize::MAX), 5), 1..5);
+
+        // Positions wholly past the end select nothing
+        assert!(clamp(&(9..20), 5).is_empty());
+        assert!(clamp(&(0..1), 0).is_empty());
+    }
+
+    #[test]
+    fn test_select() {
+        assert_eq!(select(&[0..1, 2..3], 4, false), vec![0..1, 2..3]);
+        assert_eq!(select(&[0..1, 2..3], 4, true), vec![1..2, 3..4]);
+        assert_eq!(select(&[1..3], 4, true), vec![0..1, 3..4]);
+
+        // Ranges past the end of the line are ignored
+        assert_eq!(select(&[1..10], 3, true), vec![0..1]);
+        assert_eq!(select(&[5..6], 2, true), vec![0..1, 1..2]);
+        assert!(select(&[0..4], 4, true).is_empty());
+        assert!(select(&[0..1], 0, true).is_empty());
+    }
+
+    #[test]
+    fn test_unescape() {
+        assert_eq!(unescape(","), ",");
+        assert_eq!(unescape(" | "), " | ");
+        assert_eq!(unescape("\\n"), "\n");
+        assert_eq!(unescape("\\t"), "\t");
+        assert_eq!(unescape("a\\\\b"), "a\\b");
+        assert_eq!(unescape("\\x"), "\\x");
+        assert_eq!(unescape("x\\"), "x\\");
     }
 }
@@ -1,6 +1,11 @@
-use crate::Extract::*;
-use clap::{App, Arg, ArgGroup};
-use std::{clone, error::Error, ops::Range};
+use self::Extract::*;
+use clap::{App, Arg};
+use std::{
+    error::Error,
+    fs::File,
+    io::{self, BufRead, BufReader},
+    ops::Range,
+};
 
 type MyResult<T> = Result<T, Box<dyn Error>>;
 type PositionList = Vec<Range<usize>>;
@@ -99,7 +104,6 @@ fn parse_pos(range: &str) -> MyResult<PositionList> {
     let mut buffer: PositionList = vec![];
     let temp = range.split(',');
     for r in temp {
-        let result: Range<usize>;
         let numbers = r.split('-').collect::<Vec<_>>();
         let numbers = numbers
             .into_iter()
@@ -147,14 +151,73 @@ fn parse_pos(range: &str) -> MyResult<PositionList> {
     Ok(buffer)
 }
 
+fn open(filename: &str) -> MyResult<Box<dyn BufRead>> {
+    match filename {
+        "-" => Ok(Box::new(BufReader::new(io::stdin()))),
+        _ => Ok(Box::new(BufReader::new(File::open(filename)?))),
+    }
+}
+
 pub fn run(config: Config) -> MyResult<()> {
-    println!("{:#?}", &config);
+    for filename in &config.files {
+        match open(filename) {
+            Err(err) => eprintln!("{}: {}", filename, err),
+            Ok(file) => {
+                for line in file.lines() {
+                    let line = line?;
+                    let output = match &config.extract {
+                        Bytes(byte_pos) => extract_bytes(&line, byte_pos),
+                        Chars(char_pos) => extract_chars(&line, char_pos),
+                        Fields(field_pos) => {
+                            let delimiter = (config.delimiter as char).to_string();
+                            extract_fields(&line, &delimiter, field_pos).join(&delimiter)
+                        }
+                    };
+                    println!("{}", output);
+                }
+            }
+        }
+    }
     Ok(())
 }
 
+/// Clamp a range to the length of what it indexes
+fn clamp(range: &Range<usize>, len: usize) -> Range<usize> {
+    range.start.min(len)..range.end.min(len)
+}
+
+fn extract_bytes(line: &str, byte_pos: &[Range<usize>]) -> String {
+    let bytes = line.as_bytes();
+    let mut selected: Vec<u8> = vec![];
+    for range in byte_pos {
+        selected.extend_from_slice(&bytes[clamp(range, bytes.len())]);
+    }
+    String::from_utf8_lossy(&selected).into_owned()
+}
+
+fn extract_chars(line: &str, char_pos: &[Range<usize>]) -> String {
+    let chars = line.chars().collect::<Vec<char>>();
+    let mut selected = String::new();
+    for range in char_pos {
+        selected.extend(&chars[clamp(range, chars.len())]);
+    }
+    selected
+}
+
+fn extract_fields<'a>(line: &'a str, delimiter: &str, field_pos: &[Range<usize>]) -> Vec<&'a str> {
+    let fields = line.split(delimiter).collect::<Vec<&str>>();
+    let mut selected: Vec<&str> = vec![];
+    for range in field_pos {
+        selected.extend_from_slice(&fields[clamp(range, fields.len())]);
+    }
+    selected
+}
+
 #[cfg(test)]
+#[allow(clippy::single_range_in_vec_init)]
 mod unit_tests {
-    use super::parse_pos;
+    use super::{extract_bytes, extract_chars, extract_fields, open, parse_pos};
+    use std::io::BufRead;
 
     #[test]
     fn test_parse_pos() {
@@ -267,4 +330,52 @@ mod unit_tests {
         assert!(res.is_ok());
         assert_eq!(res.unwrap(), vec![14..15, 18..20]);
     }
+
+    #[test]
+    fn test_open() {
+        // "-" is stdin, and a missing file is an error for the caller to report
+        assert!(open("-").is_ok());
+        assert!(open("tests/inputs/missing.txt").is_err());
+
+        let lines: Vec<_> = open("tests/inputs/hello.txt")
+            .unwrap()
+            .lines()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(lines, ["héllo"]);
+    }
+
+    #[test]
+    fn test_extract_bytes() {
+        assert_eq!(extract_bytes("ábc", &[0..1]), "�".to_string());
+        assert_eq!(extract_bytes("ábc", &[0..2]), "á".to_string());
+        assert_eq!(extract_bytes("ábc", &[0..4]), "ábc".to_string());
+        assert_eq!(extract_bytes("ábc", &[3..4, 2..3]), "cb".to_string());
+        assert_eq!(extract_bytes("ábc", &[0..2, 5..6]), "á".to_string());
+    }
+
+    #[test]
+    fn test_extract_chars() {
+        assert_eq!(extract_chars("", &[0..1]), "".to_string());
+        assert_eq!(extract_chars("ábc", &[0..1, 2..3]), "ác".to_string());
+        assert_eq!(extract_chars("ábc", &[2..3, 1..2]), "cb".to_string());
+        assert_eq!(extract_chars("ábc", &[0..1, 1..2, 4..5]), "áb".to_string());
+        assert_eq!(extract_chars("ábc", &[1..10]), "bc".to_string());
+    }
+
+    #[test]
+    fn test_extract_fields() {
+        let line = "Captain\tSham\t12345";
+        assert_eq!(extract_fields(line, "\t", &[0..1]), &["Captain"]);
+        assert_eq!(
+            extract_fields(line, "\t", &[0..1, 2..3]),
+            &["Captain", "12345"]
+        );
+        assert_eq!(extract_fields(line, "\t", &[0..1, 3..4]), &["Captain"]);
+        assert_eq!(
+            extract_fields(line, "\t", &[1..2, 0..1]),
+            &["Sham", "Captain"]
+        );
+        assert_eq!(extract_fields(line, ",", &[0..1]), &[line]);
+    }
 }
@@ -9,6 +9,8 @@ const PRG: &str = "cutr";
 const CSV: &str = "tests/inputs/movies1.csv";
 const TSV: &str = "tests/inputs/movies1.tsv";
 const BOOKS: &str = "tests/inputs/books.tsv";
+const MIXED: &str = "tests/inputs/mixed.tsv";
+const HELLO: &str = "tests/inputs/hello.txt";
 
 // --------------------------------------------------
 fn random_string() -> String {
@@ -35,13 +37,28 @@ fn skips_bad_file() -> TestResult {
     let bad = gen_bad_file();
     let expected = format!("{}: .* [(]os error 2[)]", bad);
     Command::cargo_bin(PRG)?
-        .args(&["-f", "1", CSV, &bad, TSV])
+        .args(["-f", "1", CSV, &bad, TSV])
         .assert()
         .success()
         .stderr(predicate::str::is_match(expected)?);
     Ok(())
 }
 
+// --------------------------------------------------
+#[test]
+fn stdin_and_file() -> TestResult {
+    // "-" reads stdin, and a bad file in between does not stop the rest
+    let bad = gen_bad_file();
+    Command::cargo_bin(PRG)?
+        .args(["-c", "1-5", "-", &bad, HELLO])
+        .write_stdin("from stdin\n")
+        .assert()
+        .success()
+        .stdout("from \nhéllo\n")
+        .stderr(predicate::str::contains(bad));
+    Ok(())
+}
+
 // --------------------------------------------------
 fn dies(args: &[&str], expected: &str) -> TestResult {
     Command::cargo_bin(PRG)?
@@ -110,7 +127,7 @@ fn dies_bad_delimiter() -> TestResult {
 #[test]
 fn dies_chars_bytes_fields() -> TestResult {
     Command::cargo_bin(PRG)?
-        .args(&[CSV, "-c", "1", "-f", "1", "-b", "1"])
+        .args([CSV, "-c", "1", "-f", "1", "-b", "1"])
         .assert()
         .failure();
     Ok(())
@@ -120,7 +137,7 @@ fn dies_chars_bytes_fields() -> TestResult {
 #[test]
 fn dies_bytes_fields() -> TestResult {
     Command::cargo_bin(PRG)?
-        .args(&[CSV, "-f", "1", "-b", "1"])
+        .args([CSV, "-f", "1", "-b", "1"])
         .assert()
         .failure();
     Ok(())
@@ -130,7 +147,7 @@ fn dies_bytes_fields() -> TestResult {
 #[test]
 fn dies_chars_fields() -> TestResult {
     Command::cargo_bin(PRG)?
-        .args(&[CSV, "-c", "1", "-f", "1"])
+        .args([CSV, "-c", "1", "-f", "1"])
         .assert()
         .failure();
     Ok(())
@@ -140,7 +157,7 @@ fn dies_chars_fields() -> TestResult {
 #[test]
 fn dies_chars_bytes() -> TestResult {
     Command::cargo_bin(PRG)?
-        .args(&[CSV, "-c", "1", "-b", "1"])
+        .args([CSV, "-c", "1", "-b", "1"])
         .assert()
         .failure();
     Ok(())
@@ -337,3 +354,297 @@ fn tsv_c1_8() -> TestResult {
 fn repeated_value() -> TestResult {
     run(&[BOOKS, "-c", "1,1"], "tests/expected/books.c1,1.out")
 }
+
+// --------------------------------------------------
+#[test]
+fn output_delimiter_newline() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["-f", "1-3", "--output-delimiter", "\\n", BOOKS])
+        .assert()
+        .success()
+        .stdout(
+            "Author\nYear\nTitle\n\
+            Émile Zola\n1865\nLa Confession de Claude\n\
+            Samuel Beckett\n1952\nWaiting for Godot\n\
+            Jules Verne\n1870\n20,000 Leagues Under the Sea\n",
+        );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn complement_fields() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["-f", "1,3", "--complement"])
+        .write_stdin("a\tb\tc\td\n1\t2\t3\t4\n")
+        .assert()
+        .success()
+        .stdout("b\td\n2\t4\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn complement_bytes() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["-b", "2-3,9", "--complement"])
+        .write_stdin("abcdef\nxy\n")
+        .assert()
+        .success()
+        .stdout("adef\nx\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn complement_chars() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["-c", "1", "--complement"])
+        .write_stdin("ábc\né\n")
+        .assert()
+        .success()
+        .stdout("bc\n\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn output_delimiter_tsv_to_csv() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args([TSV, "-f", "1-3", "--output-delimiter", ","])
+        .assert()
+        .success()
+        .stdout(
+            "title,year,director\n\
+            The Blues Brothers,1980,John Landis\n\
+            Les Misérables,2019,Tom Hooper\n",
+        );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn output_delimiter_multichar() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args([TSV, "-f", "2,1", "--output-delimiter", " :: "])
+        .assert()
+        .success()
+        .stdout("year :: title\n1980 :: The Blues Brothers\n2019 :: Les Misérables\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn mixed_f2() -> TestResult {
+    run(&[MIXED, "-f", "2"], "tests/expected/mixed.tsv.f2.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn mixed_f2_only_delimited() -> TestResult {
+    run(
+        &[MIXED, "-s", "-f", "2"],
+        "tests/expected/mixed.tsv.f2.s.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn mixed_c1_3_only_delimited() -> TestResult {
+    // -s has no effect outside of field mode
+    run(
+        &[MIXED, "--only-delimited", "-c", "1-3"],
+        "tests/expected/mixed.tsv.c1-3.s.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn tsv_f2_open() -> TestResult {
+    run(&[TSV, "-f", "2-"], "tests/expected/movies1.tsv.f2-.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn tsv_c_open_3() -> TestResult {
+    run(&[TSV, "-c", "-3"], "tests/expected/movies1.tsv.c-3.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn tsv_b1_2_10_open() -> TestResult {
+    run_lossy(
+        &[TSV, "-b", "1-2,10-"],
+        "tests/expected/movies1.tsv.b1-2,10-.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn regex_delim() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args([
+            "--regex-delim",
+            r"\s+",
+            "-f",
+            "1-3",
+            "--output-delimiter",
+            ",",
+        ])
+        .write_stdin("a   b\tc\n")
+        .assert()
+        .success()
+        .stdout("a,b,c\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn whitespace_delim() -> TestResult {
+    // The fields are joined with --delim
+    Command::cargo_bin(PRG)?
+        .args(["-w", "-f", "3,1"])
+        .write_stdin("a   b\tc\nno-spaces\n")
+        .assert()
+        .success()
+        .stdout("c\ta\nno-spaces\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_regex_delim() -> TestResult {
+    dies(
+        &["--regex-delim", "*", "-f", "1"],
+        r#"Invalid --regex-delim "*""#,
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn field_past_end() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args([CSV, "-d", ",", "-f", "5"])
+        .assert()
+        .success()
+        .stdout("\n\n\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn chars_past_end() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["-c", "10-20"])
+        .write_stdin("short\nlonger line\n")
+        .assert()
+        .success()
+        .stdout("\nne\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn bytes_past_end() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["-b", "10-20,30"])
+        .write_stdin("short\n")
+        .assert()
+        .success()
+        .stdout("\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn bytes_split_code_point() -> TestResult {
+    // Half of "é" is replaced rather than emitted as a broken sequence
+    Command::cargo_bin(PRG)?
+        .args([HELLO, "-b", "1-2"])
+        .assert()
+        .success()
+        .stdout("h\u{FFFD}\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn bytes_whole_code_point() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args([HELLO, "-b", "1-3"])
+        .assert()
+        .success()
+        .stdout("hé\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn chars_code_points() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args([HELLO, "-c", "1-2"])
+        .assert()
+        .success()
+        .stdout("hé\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn csv_quoted_delimiter() -> TestResult {
+    // The quoted comma stays in its field, which the writer quotes again
+    Command::cargo_bin(PRG)?
+        .args(["--csv", "-f", "2"])
+        .write_stdin("a,\"b,c\",d\n")
+        .assert()
+        .success()
+        .stdout("\"b,c\"\n");
+    Command::cargo_bin(PRG)?
+        .args(["--csv", "-f", "2-3"])
+        .write_stdin("a,\"b,c\",d\n")
+        .assert()
+        .success()
+        .stdout("\"b,c\",d\n");
+
+    // Only the fields that need it are quoted
+    Command::cargo_bin(PRG)?
+        .args(["--csv", "-f", "1,3"])
+        .write_stdin("a,\"b,c\",d\n")
+        .assert()
+        .success()
+        .stdout("a,d\n");
+    Command::cargo_bin(PRG)?
+        .args(["--csv", "-f", "2"])
+        .write_stdin("a,\"say \"\"hi\"\"\",d\n")
+        .assert()
+        .success()
+        .stdout("\"say \"\"hi\"\"\"\n");
+
+    // An output delimiter joins the fields as they are
+    Command::cargo_bin(PRG)?
+        .args(["--csv", "-f", "2-3", "--output-delimiter", " "])
+        .write_stdin("a,\"b,c\",d\n")
+        .assert()
+        .success()
+        .stdout("b,c d\n");
+
+    // Any other delimiter can still be given
+    Command::cargo_bin(PRG)?
+        .args(["--csv", "-d", ";", "-f", "2"])
+        .write_stdin("a;\"b;c\";d\n")
+        .assert()
+        .success()
+        .stdout("\"b;c\"\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_csv_regex_delim() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--csv", "-w", "-f", "1", CSV])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+    Ok(())
+}
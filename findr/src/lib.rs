@@ -1,11 +1,24 @@
 use crate::EntryType::*;
 use clap::{App, Arg};
-use regex::Regex;
-use std::error::Error;
+use regex::{Regex, RegexBuilder};
+use std::{
+    error::Error,
+    fs, io,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
 use walkdir::{DirEntry, WalkDir};
 
 type MyResult<T> = Result<T, Box<dyn Error>>;
 
+/// An earlier solution, built with the tests so that its own keep running
+#[cfg(test)]
+#[allow(dead_code)]
+#[path = "lib_mysol.rs"]
+mod mysol;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
 #[derive(Debug, Eq, PartialEq)]
 enum EntryType {
     Dir,
@@ -13,11 +26,54 @@ enum EntryType {
     Link,
 }
 
+/// What --sort orders the results by
+#[derive(Debug, Eq, PartialEq)]
+enum SortKey {
+    Name,
+    Size,
+    Time,
+}
+
+/// A numeric test written like find's `+N` (more than N), `-N` (fewer
+/// than N) or `N` (exactly N)
+#[derive(Debug, Eq, PartialEq)]
+enum Comparison {
+    Above(u64),
+    Below(u64),
+    Exactly(u64),
+}
+
+impl Comparison {
+    fn matches(&self, val: u64) -> bool {
+        match self {
+            Comparison::Above(n) => val > *n,
+            Comparison::Below(n) => val < *n,
+            Comparison::Exactly(n) => val == *n,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Config {
     paths: Vec<String>,
     names: Vec<Regex>,
+    path_patterns: Vec<Regex>,
     entry_types: Vec<EntryType>,
+    min_size: Option<u64>,
+    max_size: Option<u64>,
+    size: Option<Comparison>,
+    relative_to: Option<PathBuf>,
+    links: Option<Comparison>,
+    long: bool,
+    quit_after: Option<Duration>,
+    min_depth: Option<usize>,
+    max_depth: Option<usize>,
+    print0: bool,
+    mtime: Option<Comparison>,
+    newer: Option<SystemTime>,
+    empty: bool,
+    follow: bool,
+    sort: Option<SortKey>,
 }
 
 pub fn get_args() -> MyResult<Config> {
@@ -41,6 +97,29 @@ pub fn get_args() -> MyResult<Config> {
                 .takes_value(true)
                 .help("Name"),
         )
+        .arg(
+            Arg::with_name("path_patterns")
+                .value_name("PATTERN")
+                .long("path")
+                .multiple(true)
+                .takes_value(true)
+                .number_of_values(1)
+                .help("Pattern to match against the whole path"),
+        )
+        .arg(
+            Arg::with_name("insensitive")
+                .short("i")
+                .long("ignore-case")
+                .takes_value(false)
+                .help("Match names case-insensitively"),
+        )
+        .arg(
+            Arg::with_name("name_from")
+                .value_name("FILE")
+                .long("name-from")
+                .takes_value(true)
+                .help("Read name patterns from FILE, one per line"),
+        )
         .arg(
             Arg::with_name("types")
                 .value_name("TYPE")
@@ -48,43 +127,497 @@ pub fn get_args() -> MyResult<Config> {
                 .long("type")
                 .takes_value(true)
                 .multiple(true)
-                .possible_values(&["f", "d", "l"])
+                .use_delimiter(true)
+                .possible_values(&["f", "d", "l", "all"])
                 .help("Entry type"),
         )
+        .arg(
+            Arg::with_name("min_size")
+                .value_name("SIZE")
+                .long("min-size")
+                .takes_value(true)
+                .help("Minimum size, e.g. 1K or 2M"),
+        )
+        .arg(
+            Arg::with_name("max_size")
+                .value_name("SIZE")
+                .long("max-size")
+                .takes_value(true)
+                .help("Maximum size, e.g. 1K or 2M"),
+        )
+        .arg(
+            Arg::with_name("size")
+                .value_name("SIZE")
+                .long("size")
+                .takes_value(true)
+                .allow_hyphen_values(true)
+                .help("Size: +SIZE more than, -SIZE less than, SIZE exactly, e.g. +1k"),
+        )
+        .arg(
+            Arg::with_name("mtime")
+                .value_name("DAYS")
+                .long("mtime")
+                .takes_value(true)
+                .allow_hyphen_values(true)
+                .help("Days since modified: +N more than, -N fewer than, N exactly"),
+        )
+        .arg(
+            Arg::with_name("newer")
+                .value_name("FILE")
+                .long("newer")
+                .takes_value(true)
+                .help("Modified more recently than FILE"),
+        )
+        .arg(
+            Arg::with_name("empty")
+                .long("empty")
+                .takes_value(false)
+                .help("Only empty files and directories"),
+        )
+        .arg(
+            Arg::with_name("follow")
+                .short("L")
+                .long("follow")
+                .takes_value(false)
+                .help("Follow symbolic links"),
+        )
+        .arg(
+            Arg::with_name("sort")
+                .value_name("KEY")
+                .long("sort")
+                .takes_value(true)
+                .min_values(0)
+                .require_equals(true)
+                .possible_values(&["name", "size", "time"])
+                .help("Print all results at the end, sorted by path or --sort=size|time"),
+        )
+        .arg(
+            Arg::with_name("relative_to")
+                .value_name("DIR")
+                .long("relative-to")
+                .alias("print-relative-to")
+                .takes_value(true)
+                .help("Print paths under DIR relative to it"),
+        )
+        .arg(
+            Arg::with_name("links")
+                .value_name("N")
+                .long("links")
+                .takes_value(true)
+                .allow_hyphen_values(true)
+                .help("Hard link count: +N more than, -N fewer than, N exactly"),
+        )
+        .arg(
+            Arg::with_name("long")
+                .long("long")
+                .alias("ls")
+                .takes_value(false)
+                .help("List entries in long format, like ls -l"),
+        )
+        .arg(
+            Arg::with_name("quit_after")
+                .value_name("DURATION")
+                .long("quit-after")
+                .takes_value(true)
+                .help("Stop searching after DURATION, e.g. 500ms, 10s or 2m"),
+        )
+        .arg(
+            Arg::with_name("print0")
+                .short("0")
+                .long("print0")
+                .takes_value(false)
+                .help("End each path with NUL instead of a newline, for xargs -0"),
+        )
+        .arg(
+            Arg::with_name("min_depth")
+                .value_name("N")
+                .long("min-depth")
+                .alias("mindepth")
+                .takes_value(true)
+                .help("Skip entries less than N levels below the paths"),
+        )
+        .arg(
+            Arg::with_name("max_depth")
+                .value_name("N")
+                .long("max-depth")
+                .alias("maxdepth")
+                .takes_value(true)
+                .help("Descend at most N levels below the paths"),
+        )
         .get_matches();
 
-    let names = matches
+    let insensitive = matches.is_present("insensitive");
+    let mut names = matches
         .values_of_lossy("names")
         .map(|vals| {
             vals.into_iter()
-                .map(|name| Regex::new(&name).map_err(|_| format!("Invalid --name \"{}\"", name)))
+                .map(|name| {
+                    build_name(&name, insensitive)
+                        .map_err(|_| format!("Invalid --name \"{}\"", name))
+                })
                 .collect::<Result<Vec<_>, _>>()
         })
         .transpose()?
         .unwrap_or_default();
+    if let Some(filename) = matches.value_of("name_from") {
+        names.extend(read_names(filename, insensitive)?);
+    }
 
-    let entry_types = matches
-        .values_of_lossy("types")
-        .map(|vals| {
-            vals.iter()
-                .map(|val| match val.as_str() {
-                    "d" => Dir,
-                    "f" => File,
-                    "l" => Link,
-                    _ => unreachable!("Invalid type"),
-                })
-                .collect()
+    let path_patterns = matches
+        .values_of_lossy("path_patterns")
+        .unwrap_or_default()
+        .into_iter()
+        .map(|pattern| {
+            build_name(&pattern, insensitive).map_err(|_| format!("Invalid --path \"{}\"", pattern))
         })
-        .unwrap_or_default();
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let types = matches.values_of_lossy("types").unwrap_or_default();
+    if types.len() > 1 && types.iter().any(|val| val == "all") {
+        return Err(From::from("--type all cannot be combined with other types"));
+    }
+    // No types and "all" alike leave entries unfiltered
+    let entry_types = types
+        .iter()
+        .filter(|val| *val != "all")
+        .map(|val| match val.as_str() {
+            "d" => Dir,
+            "f" => File,
+            "l" => Link,
+            _ => unreachable!("Invalid type"),
+        })
+        .collect();
+
+    let min_size = matches
+        .value_of("min_size")
+        .map(|val| parse_size(val).map_err(|_| format!("Invalid --min-size \"{}\"", val)))
+        .transpose()?;
+    let max_size = matches
+        .value_of("max_size")
+        .map(|val| parse_size(val).map_err(|_| format!("Invalid --max-size \"{}\"", val)))
+        .transpose()?;
+
+    let size = matches
+        .value_of("size")
+        .map(|val| parse_size_comparison(val).map_err(|_| format!("Invalid --size \"{}\"", val)))
+        .transpose()?;
+
+    let mtime = matches
+        .value_of("mtime")
+        .map(|val| parse_comparison(val).map_err(|_| format!("Invalid --mtime \"{}\"", val)))
+        .transpose()?;
+    let newer = matches
+        .value_of("newer")
+        .map(|filename| {
+            fs::metadata(filename)
+                .and_then(|meta| meta.modified())
+                .map_err(|e| format!("--newer {}: {}", filename, e))
+        })
+        .transpose()?;
+
+    let links = matches
+        .value_of("links")
+        .map(|val| parse_comparison(val).map_err(|_| format!("Invalid --links \"{}\"", val)))
+        .transpose()?;
+
+    let quit_after = matches
+        .value_of("quit_after")
+        .map(|val| parse_duration(val).map_err(|_| format!("Invalid --quit-after \"{}\"", val)))
+        .transpose()?;
+
+    let min_depth = matches
+        .value_of("min_depth")
+        .map(|val| {
+            val.parse::<usize>()
+                .map_err(|_| format!("Invalid --min-depth \"{}\"", val))
+        })
+        .transpose()?;
+    let max_depth = matches
+        .value_of("max_depth")
+        .map(|val| {
+            val.parse::<usize>()
+                .map_err(|_| format!("Invalid --max-depth \"{}\"", val))
+        })
+        .transpose()?;
+    if let (Some(min), Some(max)) = (min_depth, max_depth) {
+        if min > max {
+            return Err(format!(
+                "--min-depth ({}) must not be greater than --max-depth ({})",
+                min, max
+            )
+            .into());
+        }
+    }
 
     Ok(Config {
         paths: matches.values_of_lossy("paths").unwrap(),
         names,
+        path_patterns,
         entry_types,
+        min_size,
+        max_size,
+        size,
+        relative_to: matches.value_of("relative_to").map(PathBuf::from),
+        links,
+        long: matches.is_present("long"),
+        quit_after,
+        min_depth,
+        max_depth,
+        print0: matches.is_present("print0"),
+        mtime,
+        newer,
+        empty: matches.is_present("empty"),
+        follow: matches.is_present("follow"),
+        sort: matches
+            .is_present("sort")
+            .then(|| match matches.value_of("sort") {
+                Some("size") => SortKey::Size,
+                Some("time") => SortKey::Time,
+                _ => SortKey::Name,
+            }),
     })
 }
 
-pub fn run(config: Config) -> MyResult<()> {
+/// Compile a name pattern, ignoring case if asked
+fn build_name(pattern: &str, insensitive: bool) -> Result<Regex, regex::Error> {
+    RegexBuilder::new(pattern)
+        .case_insensitive(insensitive)
+        .build()
+}
+
+/// Read one name regex per line from a file, skipping blank lines
+fn read_names(filename: &str, insensitive: bool) -> MyResult<Vec<Regex>> {
+    let contents = fs::read_to_string(filename).map_err(|e| format!("{}: {}", filename, e))?;
+    contents
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim_end_matches('\r')))
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(num, line)| {
+            build_name(line, insensitive)
+                .map_err(|_| format!("{}:{}: Invalid --name \"{}\"", filename, num, line).into())
+        })
+        .collect()
+}
+
+/// Split a leading "+" or "-" from a value, returning the kind of
+/// [`Comparison`] it asks for and the rest of the value
+fn split_sign(val: &str) -> (fn(u64) -> Comparison, &str) {
+    match val.as_bytes().first() {
+        Some(b'+') => (Comparison::Above, &val[1..]),
+        Some(b'-') => (Comparison::Below, &val[1..]),
+        _ => (Comparison::Exactly, val),
+    }
+}
+
+/// Parse "+N", "-N" or "N" into a [`Comparison`]
+fn parse_comparison(val: &str) -> MyResult<Comparison> {
+    let (make, num) = split_sign(val);
+    if num.is_empty() || !num.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(From::from(val));
+    }
+    Ok(make(num.parse()?))
+}
+
+/// Parse "+SIZE", "-SIZE" or "SIZE" into a [`Comparison`] of bytes,
+/// where SIZE is anything [`parse_size`] accepts
+fn parse_size_comparison(val: &str) -> MyResult<Comparison> {
+    let (make, size) = split_sign(val);
+    parse_size(size).map(make).map_err(|_| From::from(val))
+}
+
+/// Parse a duration like "500ms", "10s", "2m" or "1h", where a bare
+/// number is taken as seconds
+fn parse_duration(val: &str) -> MyResult<Duration> {
+    let split = val.find(|c: char| !c.is_ascii_digit()).unwrap_or(val.len());
+    let (num, unit) = val.split_at(split);
+    if num.is_empty() {
+        return Err(From::from(val));
+    }
+    let num: u64 = num.parse()?;
+    match unit {
+        "ms" => Ok(Duration::from_millis(num)),
+        "" | "s" => Ok(Duration::from_secs(num)),
+        "m" => num
+            .checked_mul(60)
+            .map(Duration::from_secs)
+            .ok_or_else(|| From::from(val)),
+        "h" => num
+            .checked_mul(60 * 60)
+            .map(Duration::from_secs)
+            .ok_or_else(|| From::from(val)),
+        _ => Err(From::from(val)),
+    }
+}
+
+/// Whole days between `modified` and `now`, rounded down like find's
+/// -mtime, with times in the future counted as zero days old
+fn age_in_days(modified: SystemTime, now: SystemTime) -> u64 {
+    now.duration_since(modified)
+        .map_or(0, |age| age.as_secs() / SECONDS_PER_DAY)
+}
+
+/// The distinct starting paths that exist, in the order given, and how
+/// many distinct ones were missing, each reported on stderr
+fn check_roots(paths: &[String], follow: bool) -> (Vec<&Path>, usize) {
+    let mut seen: Vec<&Path> = vec![];
+    let mut roots = vec![];
+    let mut num_missing = 0;
+    for path in paths.iter().map(Path::new) {
+        if seen.contains(&path) {
+            continue;
+        }
+        seen.push(path);
+        let meta = if follow {
+            fs::metadata(path)
+        } else {
+            fs::symlink_metadata(path)
+        };
+        match meta {
+            Ok(_) => roots.push(path),
+            Err(e) => {
+                eprintln!("findr: {}: {}", path.display(), error_message(&e));
+                num_missing += 1;
+            }
+        }
+    }
+    (roots, num_missing)
+}
+
+/// An I/O error's message without the trailing " (os error N)"
+fn error_message(e: &io::Error) -> String {
+    let message = e.to_string();
+    match message.find(" (os error ") {
+        Some(i) => message[..i].to_string(),
+        None => message,
+    }
+}
+
+/// Sort entries by path, or by size or modification time with the path
+/// breaking ties
+fn sort_entries(entries: &mut [DirEntry], key: &SortKey) {
+    match key {
+        SortKey::Name => entries.sort_by(|a, b| a.path().cmp(b.path())),
+        SortKey::Size => entries.sort_by_cached_key(|entry| {
+            let size = entry.metadata().map_or(0, |meta| meta.len());
+            (size, entry.path().to_path_buf())
+        }),
+        SortKey::Time => entries.sort_by_cached_key(|entry| {
+            let modified = entry.metadata().ok().and_then(|meta| meta.modified().ok());
+            (modified, entry.path().to_path_buf())
+        }),
+    }
+}
+
+/// Print the results one per line, or each ended by NUL for --print0
+fn print_results(results: &[String], print0: bool) {
+    if print0 {
+        for result in results {
+            print!("{}\0", result);
+        }
+    } else {
+        println!("{}", results.join("\n"));
+    }
+}
+
+/// Whether an entry is a zero-length file or a directory with no children
+fn is_empty(entry: &DirEntry) -> bool {
+    let file_type = entry.file_type();
+    if file_type.is_dir() {
+        fs::read_dir(entry.path()).is_ok_and(|mut children| children.next().is_none())
+    } else if file_type.is_file() {
+        entry.metadata().is_ok_and(|meta| meta.len() == 0)
+    } else {
+        false
+    }
+}
+
+/// Format an entry like a line of `ls -l`: type and permissions, link
+/// count, size and the path, with a symlink followed by `-> target`
+fn format_long(entry: &DirEntry, path: String) -> String {
+    let meta = match entry.path().symlink_metadata() {
+        Ok(meta) => meta,
+        Err(_) => return path,
+    };
+    let file_type = if meta.file_type().is_symlink() {
+        'l'
+    } else if meta.is_dir() {
+        'd'
+    } else {
+        '-'
+    };
+    let mut line = format!(
+        "{}{} {:>3} {:>8} {}",
+        file_type,
+        format_mode(&meta),
+        link_count(&meta).unwrap_or(1),
+        meta.len(),
+        path
+    );
+    if file_type == 'l' {
+        if let Ok(target) = fs::read_link(entry.path()) {
+            line.push_str(&format!(" -> {}", target.display()));
+        }
+    }
+    line
+}
+
+#[cfg(unix)]
+fn format_mode(meta: &fs::Metadata) -> String {
+    use std::os::unix::fs::PermissionsExt;
+    let mode = meta.permissions().mode();
+    "rwxrwxrwx"
+        .chars()
+        .enumerate()
+        .map(|(i, c)| if mode & (0o400 >> i) == 0 { '-' } else { c })
+        .collect()
+}
+
+#[cfg(not(unix))]
+fn format_mode(meta: &fs::Metadata) -> String {
+    if meta.permissions().readonly() {
+        "r--r--r--".to_string()
+    } else {
+        "rw-rw-rw-".to_string()
+    }
+}
+
+#[cfg(unix)]
+fn link_count(meta: &fs::Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Some(meta.nlink())
+}
+
+#[cfg(not(unix))]
+fn link_count(_meta: &fs::Metadata) -> Option<u64> {
+    None
+}
+
+/// Parse a size like "512", "1K", "2M" or "1G" into a number of bytes,
+/// using 1024-based units
+fn parse_size(val: &str) -> MyResult<u64> {
+    let (num, unit) = match val.char_indices().last() {
+        Some((i, c)) if c.is_ascii_alphabetic() => (&val[..i], c.to_ascii_uppercase()),
+        _ => (val, 'B'),
+    };
+    let multiplier = match unit {
+        'B' => 1,
+        'K' => 1024,
+        'M' => 1024 * 1024,
+        'G' => 1024 * 1024 * 1024,
+        _ => return Err(From::from(val)),
+    };
+    match num.parse::<u64>() {
+        Ok(n) if num.bytes().all(|b| b.is_ascii_digit()) => {
+            n.checked_mul(multiplier).ok_or_else(|| From::from(val))
+        }
+        _ => Err(From::from(val)),
+    }
+}
+
+/// Search the paths, giving the number that do not exist. The others are
+/// still searched, but the exit status should say that not all of them were.
+pub fn run(config: Config) -> MyResult<usize> {
     let type_filter = |entry: &DirEntry| {
         config.entry_types.is_empty()
             || config
@@ -104,23 +637,284 @@ pub fn run(config: Config) -> MyResult<()> {
                 .iter()
                 .any(|re| re.is_match(&entry.file_name().to_string_lossy()))
     };
-    for path in &config.paths {
-        let entries = WalkDir::new(path)
+    let path_filter = |entry: &DirEntry| {
+        config.path_patterns.is_empty()
+            || config
+                .path_patterns
+                .iter()
+                .any(|re| re.is_match(&entry.path().to_string_lossy()))
+    };
+    let size_filter = |entry: &DirEntry| {
+        if config.min_size.is_none() && config.max_size.is_none() && config.size.is_none() {
+            return true;
+        }
+        // Only regular files are sized unless another type was asked for
+        if !entry.file_type().is_file() && config.entry_types.is_empty() {
+            return false;
+        }
+        entry.metadata().is_ok_and(|meta| {
+            config.min_size.is_none_or(|min| meta.len() >= min)
+                && config.max_size.is_none_or(|max| meta.len() <= max)
+                && config
+                    .size
+                    .as_ref()
+                    .is_none_or(|size| size.matches(meta.len()))
+        })
+    };
+
+    let links_filter = |entry: &DirEntry| match &config.links {
+        None => true,
+        Some(links) => entry
+            .metadata()
+            .ok()
+            .and_then(|meta| link_count(&meta))
+            .is_some_and(|count| links.matches(count)),
+    };
+
+    let now = SystemTime::now();
+    let time_filter = |entry: &DirEntry| {
+        if config.mtime.is_none() && config.newer.is_none() {
+            return true;
+        }
+        entry
+            .metadata()
+            .ok()
+            .and_then(|meta| meta.modified().ok())
+            .is_some_and(|modified| {
+                config
+                    .mtime
+                    .as_ref()
+                    .is_none_or(|days| days.matches(age_in_days(modified, now)))
+                    && config.newer.is_none_or(|time| modified > time)
+            })
+    };
+
+    let format_entry = |entry: &DirEntry| {
+        let path = match &config.relative_to {
+            Some(dir) => match entry.path().strip_prefix(dir) {
+                Ok(rel) if rel.as_os_str().is_empty() => ".".to_string(),
+                Ok(rel) => rel.display().to_string(),
+                Err(_) => entry.path().display().to_string(),
+            },
+            None => entry.path().display().to_string(),
+        };
+        if config.long {
+            format_long(entry, path)
+        } else {
+            path
+        }
+    };
+
+    let (roots, num_missing) = check_roots(&config.paths, config.follow);
+    let start = Instant::now();
+    let mut timed_out = false;
+    // With --sort, results from every path are held back until the end
+    let mut held = vec![];
+    for path in &roots {
+        // Depth 0 is the path itself
+        let mut walker = WalkDir::new(path).follow_links(config.follow);
+        if let Some(depth) = config.min_depth {
+            walker = walker.min_depth(depth);
+        }
+        if let Some(depth) = config.max_depth {
+            walker = walker.max_depth(depth);
+        }
+        let entries = walker
             .into_iter()
+            .take_while(|_| {
+                timed_out = config
+                    .quit_after
+                    .is_some_and(|limit| start.elapsed() >= limit);
+                !timed_out
+            })
             .filter_map(|e| match e {
                 Err(e) => {
-                    eprintln!("{}", e);
+                    match (e.path(), e.loop_ancestor()) {
+                        (Some(path), Some(ancestor)) => eprintln!(
+                            "findr: {}: filesystem loop back to {}, not following",
+                            path.display(),
+                            ancestor.display()
+                        ),
+                        _ => eprintln!("{}", e),
+                    }
                     None
                 }
                 Ok(entry) => Some(entry),
             })
             .filter(type_filter)
             .filter(name_filter)
-            .map(|entry| entry.path().display().to_string())
+            .filter(path_filter)
+            .filter(size_filter)
+            .filter(links_filter)
+            .filter(time_filter)
+            .filter(|entry| !config.empty || is_empty(entry))
             .collect::<Vec<_>>();
 
-        println!("{}", entries.join("\n"));
+        if config.sort.is_some() {
+            held.extend(entries);
+        } else {
+            let results: Vec<_> = entries.iter().map(format_entry).collect();
+            print_results(&results, config.print0);
+        }
+
+        if timed_out {
+            // Whatever matched in time is still printed
+            eprintln!(
+                "findr: search stopped after {:?}, results may be incomplete",
+                config.quit_after.unwrap_or_default()
+            );
+            break;
+        }
     }
 
-    Ok(())
+    if let Some(key) = &config.sort {
+        sort_entries(&mut held, key);
+        let results: Vec<_> = held.iter().map(format_entry).collect();
+        print_results(&results, config.print0);
+    }
+    Ok(num_missing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        age_in_days, build_name, check_roots, parse_comparison, parse_duration, parse_size,
+        parse_size_comparison, Comparison,
+    };
+    use std::{
+        path::Path,
+        time::{Duration, SystemTime},
+    };
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("0").unwrap(), 0);
+        assert_eq!(parse_size("500").unwrap(), 500);
+        assert_eq!(parse_size("1k").unwrap(), 1024);
+        assert_eq!(parse_size("1K").unwrap(), 1024);
+        assert_eq!(parse_size("2M").unwrap(), 2 * 1024 * 1024);
+        assert_eq!(parse_size("1G").unwrap(), 1024 * 1024 * 1024);
+        assert!(parse_size("").is_err());
+        assert!(parse_size("K").is_err());
+        assert!(parse_size("1Q").is_err());
+        assert!(parse_size("+1K").is_err());
+        assert!(parse_size("1.5K").is_err());
+    }
+
+    #[test]
+    fn test_parse_comparison() {
+        assert_eq!(parse_comparison("+1").unwrap(), Comparison::Above(1));
+        assert_eq!(parse_comparison("-3").unwrap(), Comparison::Below(3));
+        assert_eq!(parse_comparison("2").unwrap(), Comparison::Exactly(2));
+        assert!(parse_comparison("").is_err());
+        assert!(parse_comparison("+").is_err());
+        assert!(parse_comparison("x").is_err());
+        assert!(parse_comparison("+-1").is_err());
+
+        assert!(Comparison::Above(1).matches(2));
+        assert!(!Comparison::Above(1).matches(1));
+        assert!(Comparison::Below(2).matches(1));
+        assert!(Comparison::Exactly(2).matches(2));
+    }
+
+    #[test]
+    fn test_build_name() {
+        let re = build_name("readme", false).unwrap();
+        assert!(re.is_match("readme.txt"));
+        assert!(!re.is_match("README.md"));
+
+        let re = build_name("readme", true).unwrap();
+        assert!(re.is_match("README.md"));
+        assert!(re.is_match("ReadMe.txt"));
+        assert!(!re.is_match("read.me"));
+
+        assert!(build_name("*", true).is_err());
+    }
+
+    #[test]
+    fn test_check_roots() {
+        let paths: Vec<String> = ["tests/inputs/a", "tests/inputs/d", "tests/inputs/a/"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let (roots, num_missing) = check_roots(&paths, false);
+        assert_eq!(
+            roots,
+            [Path::new("tests/inputs/a"), Path::new("tests/inputs/d")]
+        );
+        assert_eq!(num_missing, 0);
+
+        let paths: Vec<String> = ["tests/inputs/nope", "tests/inputs/f", "tests/inputs/nope"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let (roots, num_missing) = check_roots(&paths, false);
+        assert_eq!(roots, [Path::new("tests/inputs/f")]);
+        assert_eq!(num_missing, 1);
+    }
+
+    #[test]
+    fn test_parse_size_comparison() {
+        assert_eq!(
+            parse_size_comparison("+1k").unwrap(),
+            Comparison::Above(1024)
+        );
+        assert_eq!(
+            parse_size_comparison("-500").unwrap(),
+            Comparison::Below(500)
+        );
+        assert_eq!(
+            parse_size_comparison("2M").unwrap(),
+            Comparison::Exactly(2 * 1024 * 1024)
+        );
+        assert!(parse_size_comparison("").is_err());
+        assert!(parse_size_comparison("+").is_err());
+        assert!(parse_size_comparison("+-1k").is_err());
+        assert!(parse_size_comparison("1Q").is_err());
+
+        let res = parse_size_comparison("x");
+        assert!(res.is_err());
+        assert_eq!(res.unwrap_err().to_string(), "x");
+    }
+
+    #[test]
+    fn test_mtime() {
+        // Day specs are read like --links
+        assert_eq!(parse_comparison("+7").unwrap(), Comparison::Above(7));
+        assert_eq!(parse_comparison("-1").unwrap(), Comparison::Below(1));
+        assert_eq!(parse_comparison("0").unwrap(), Comparison::Exactly(0));
+        assert!(parse_comparison("1d").is_err());
+
+        let now = SystemTime::now();
+        let day = Duration::from_secs(24 * 60 * 60);
+        assert_eq!(age_in_days(now, now), 0);
+        assert_eq!(age_in_days(now - day / 2, now), 0);
+        assert_eq!(age_in_days(now - day, now), 1);
+        assert_eq!(age_in_days(now - day * 10 - day / 2, now), 10);
+        assert_eq!(age_in_days(now + day, now), 0);
+
+        // A file modified today is less than a day old, not more than none
+        assert!(Comparison::Below(1).matches(age_in_days(now, now)));
+        assert!(!Comparison::Above(0).matches(age_in_days(now, now)));
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("10").unwrap(), Duration::from_secs(10));
+        assert_eq!(parse_duration("10s").unwrap(), Duration::from_secs(10));
+        assert_eq!(parse_duration("500ms").unwrap(), Duration::from_millis(500));
+        assert_eq!(parse_duration("2m").unwrap(), Duration::from_secs(120));
+        assert_eq!(parse_duration("1h").unwrap(), Duration::from_secs(3600));
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("s").is_err());
+        assert!(parse_duration("1.5s").is_err());
+        assert!(parse_duration("-1s").is_err());
+        assert!(parse_duration("10d").is_err());
+
+        // Too many minutes or hours to count in seconds
+        let res = parse_duration("999999999999999999m");
+        assert!(res.is_err());
+        assert_eq!(res.unwrap_err().to_string(), "999999999999999999m");
+        assert!(parse_duration("999999999999999999h").is_err());
+    }
 }
@@ -1,7 +1,8 @@
-use crate::EntryType::*;
+use self::EntryType::*;
 use clap::{App, Arg};
 use regex::Regex;
 use std::error::Error;
+use walkdir::{DirEntry, WalkDir};
 
 type MyResult<T> = Result<T, Box<dyn Error>>;
 
@@ -78,6 +79,86 @@ pub fn get_args() -> MyResult<Config> {
 }
 
 pub fn run(config: Config) -> MyResult<()> {
-    println!("{:#?}", config);
+    let paths = find_paths(&config);
+    if !paths.is_empty() {
+        println!("{}", paths.join("\n"));
+    }
     Ok(())
 }
+
+/// Walk each search path, giving the entries that pass the type and name
+/// filters and reporting those that cannot be read
+fn find_paths(config: &Config) -> Vec<String> {
+    let type_filter = |entry: &DirEntry| {
+        config.entry_types.is_empty()
+            || config.entry_types.iter().any(|t| match t {
+                Link => entry.path_is_symlink(),
+                Dir => entry.file_type().is_dir(),
+                File => entry.file_type().is_file(),
+            })
+    };
+
+    let name_filter = |entry: &DirEntry| {
+        config.names.is_empty()
+            || config
+                .names
+                .iter()
+                .any(|re| re.is_match(&entry.file_name().to_string_lossy()))
+    };
+
+    let mut paths = vec![];
+    for path in &config.paths {
+        let entries = WalkDir::new(path)
+            .into_iter()
+            .filter_map(|e| match e {
+                Err(e) => {
+                    eprintln!("{}", e);
+                    None
+                }
+                Ok(entry) => Some(entry),
+            })
+            .filter(type_filter)
+            .filter(name_filter)
+            .map(|entry| entry.path().display().to_string());
+        paths.extend(entries);
+    }
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_paths, Config, EntryType::*};
+    use regex::Regex;
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn test_find_paths() {
+        let config = |paths: &[&str], names: &[&str], entry_types| Config {
+            paths: paths.iter().map(|path| path.to_string()).collect(),
+            names: names.iter().map(|name| Regex::new(name).unwrap()).collect(),
+            entry_types,
+        };
+        let expected = |paths: &[&[&str]]| -> Vec<String> {
+            paths
+                .iter()
+                .map(|parts| {
+                    let path: PathBuf = parts.iter().collect();
+                    Path::new("tests/inputs").join(path).display().to_string()
+                })
+                .collect()
+        };
+
+        // The same files the type_f_name_txt CLI test expects
+        let mut paths = find_paths(&config(&["tests/inputs"], &[r"\.txt$"], vec![File]));
+        paths.sort();
+        assert_eq!(
+            paths,
+            expected(&[&["a", "a.txt"], &["d", "d.txt"], &["f", "f.txt"]])
+        );
+
+        // Only the directories with a matching name
+        let mut paths = find_paths(&config(&["tests/inputs"], &["^[abf]$"], vec![Dir]));
+        paths.sort();
+        assert_eq!(paths, expected(&[&["a"], &["a", "b"], &["f"]]));
+    }
+}
@@ -1,7 +1,12 @@
 use assert_cmd::Command;
 use predicates::prelude::*;
 use rand::{distributions::Alphanumeric, Rng};
-use std::{borrow::Cow, fs, path::Path};
+use std::{
+    borrow::Cow,
+    fs::{self, File},
+    path::Path,
+    time::{Duration, SystemTime},
+};
 
 type TestResult = Result<(), Box<dyn std::error::Error>>;
 
@@ -26,20 +31,30 @@ fn gen_bad_file() -> String {
 #[test]
 fn skips_bad_dir() -> TestResult {
     let bad = gen_bad_file();
-    let expected = format!("{}: .* [(]os error [23][)]", &bad);
+    let expected = format!("findr: {}: No such file or directory", &bad);
     Command::cargo_bin(PRG)?
-        .arg(&bad)
+        .args([&bad, "tests/inputs/g.csv"])
         .assert()
-        .success()
-        .stderr(predicate::str::is_match(expected)?);
+        .failure()
+        .stdout("tests/inputs/g.csv\n")
+        .stderr(format!("{}\n", expected));
     Ok(())
 }
 
+// --------------------------------------------------
+#[test]
+fn dedups_paths() -> TestResult {
+    run(
+        &["tests/inputs/a", "tests/inputs/a/", "tests/inputs/a"],
+        "tests/expected/path_a.txt",
+    )
+}
+
 // --------------------------------------------------
 #[test]
 fn dies_bad_name() -> TestResult {
     Command::cargo_bin(PRG)?
-        .args(&["--name", "*.csv"])
+        .args(["--name", "*.csv"])
         .assert()
         .failure()
         .stderr(predicate::str::contains("Invalid --name \"*.csv\""));
@@ -51,7 +66,7 @@ fn dies_bad_name() -> TestResult {
 fn dies_bad_type() -> TestResult {
     let expected = "error: 'x' isn't a valid value for '--type <TYPE>...'";
     Command::cargo_bin(PRG)?
-        .args(&["--type", "x"])
+        .args(["--type", "x"])
         .assert()
         .failure()
         .stderr(predicate::str::contains(expected));
@@ -60,14 +75,14 @@ fn dies_bad_type() -> TestResult {
 
 // --------------------------------------------------
 #[cfg(windows)]
-fn format_file_name(expected_file: &str) -> Cow<str> {
+fn format_file_name(expected_file: &str) -> Cow<'_, str> {
     // Equivalent to: Cow::Owned(format!("{}.windows", expected_file))
     format!("{}.windows", expected_file).into()
 }
 
 // --------------------------------------------------
 #[cfg(not(windows))]
-fn format_file_name(expected_file: &str) -> Cow<str> {
+fn format_file_name(expected_file: &str) -> Cow<'_, str> {
     // Equivalent to: Cow::Borrowed(expected_file)
     expected_file.into()
 }
@@ -129,6 +144,34 @@ fn type_f() -> TestResult {
     run(&["tests/inputs", "-t", "f"], "tests/expected/type_f.txt")
 }
 
+// --------------------------------------------------
+#[test]
+fn type_all() -> TestResult {
+    run(&["tests/inputs", "-t", "all"], "tests/expected/path1.txt")
+}
+
+// --------------------------------------------------
+#[test]
+fn type_all_name() -> TestResult {
+    run(
+        &["tests/inputs", "--type", "all", "-n", ".*[.]csv"],
+        "tests/expected/name_csv.txt",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_type_all_and_other() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--type", "all,f"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "--type all cannot be combined with other types",
+        ));
+    Ok(())
+}
+
 // --------------------------------------------------
 #[test]
 fn type_f_path_a() -> TestResult {
@@ -249,12 +292,43 @@ fn name_txt_path_a_d() -> TestResult {
     )
 }
 
+// --------------------------------------------------
+#[test]
+fn name_from() -> TestResult {
+    run(
+        &["tests/inputs", "--name-from", "tests/patterns/csv_mp3.txt"],
+        "tests/expected/name_csv_mp3.txt",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_name_from() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--name-from", "tests/patterns/bad.txt"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "tests/patterns/bad.txt:3: Invalid --name \"*.csv\"",
+        ));
+    Ok(())
+}
+
 // --------------------------------------------------
 #[test]
 fn name_a() -> TestResult {
     run(&["tests/inputs", "-n", "a"], "tests/expected/name_a.txt")
 }
 
+// --------------------------------------------------
+#[test]
+fn type_f_name_txt() -> TestResult {
+    run(
+        &["tests/inputs", "-t", "f", "-n", r"\.txt$"],
+        "tests/expected/type_f_name_txt.txt",
+    )
+}
+
 // --------------------------------------------------
 #[test]
 fn type_f_name_a() -> TestResult {
@@ -273,6 +347,21 @@ fn type_d_name_a() -> TestResult {
     )
 }
 
+// --------------------------------------------------
+#[test]
+fn relative_to() -> TestResult {
+    run(
+        &[
+            "tests/inputs/a",
+            "--type",
+            "f",
+            "--relative-to",
+            "tests/inputs/a/b",
+        ],
+        "tests/expected/relative_to_a_b.txt",
+    )
+}
+
 // --------------------------------------------------
 #[test]
 fn path_g() -> TestResult {
@@ -293,7 +382,7 @@ fn unreadable_dir() -> TestResult {
     //permissions.set_mode(0o000);
 
     std::process::Command::new("chmod")
-        .args(&["000", dirname])
+        .args(["000", dirname])
         .status()
         .expect("failed");
 
@@ -313,3 +402,472 @@ fn unreadable_dir() -> TestResult {
     assert!(stderr.contains("cant-touch-this: Permission denied"));
     Ok(())
 }
+
+// --------------------------------------------------
+fn sized_files(name: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let dir = std::env::temp_dir().join(format!("findr-{}-{}", name, std::process::id()));
+    fs::create_dir_all(&dir)?;
+    fs::write(dir.join("small.txt"), vec![b'x'; 100])?;
+    fs::write(dir.join("medium.txt"), vec![b'x'; 2048])?;
+    fs::write(dir.join("large.txt"), vec![b'x'; 10 * 1024])?;
+    Ok(dir.display().to_string())
+}
+
+// --------------------------------------------------
+fn run_sized(dir: &str, args: &[&str], expected: &[&str]) -> TestResult {
+    let cmd = Command::cargo_bin(PRG)?
+        .arg(dir)
+        .args(args)
+        .assert()
+        .success();
+    let stdout = String::from_utf8(cmd.get_output().stdout.clone())?;
+    let mut lines: Vec<&str> = stdout.split('\n').filter(|s| !s.is_empty()).collect();
+    lines.sort();
+
+    let mut expected: Vec<String> = expected
+        .iter()
+        .map(|name| Path::new(dir).join(name).display().to_string())
+        .collect();
+    expected.sort();
+
+    assert_eq!(lines, expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn min_max_size() -> TestResult {
+    let dir = sized_files("min-max-size")?;
+    run_sized(&dir, &["--min-size", "1K"], &["medium.txt", "large.txt"])?;
+    run_sized(&dir, &["--max-size", "2K"], &["small.txt", "medium.txt"])?;
+    run_sized(
+        &dir,
+        &["--min-size", "1k", "--max-size", "4K"],
+        &["medium.txt"],
+    )?;
+    fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn size() -> TestResult {
+    let dir = sized_files("size")?;
+    run_sized(
+        &dir,
+        &["-t", "f", "--size", "+1k"],
+        &["medium.txt", "large.txt"],
+    )?;
+    run_sized(&dir, &["--size", "-2k"], &["small.txt"])?;
+    run_sized(&dir, &["--size", "2K"], &["medium.txt"])?;
+    run_sized(
+        &dir,
+        &["--size", "+1k", "--max-size", "4k"],
+        &["medium.txt"],
+    )?;
+    fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_size() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--size", "+1Q"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid --size \"+1Q\""));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_min_size() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--min-size", "1Q"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid --min-size \"1Q\""));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+#[cfg(unix)]
+fn links() -> TestResult {
+    let dir = std::env::temp_dir().join(format!("findr-links-{}", std::process::id()));
+    fs::create_dir_all(&dir)?;
+    fs::write(dir.join("linked.txt"), "")?;
+    fs::hard_link(dir.join("linked.txt"), dir.join("alias.txt"))?;
+    fs::write(dir.join("single.txt"), "")?;
+    let dir = dir.display().to_string();
+
+    run_sized(
+        &dir,
+        &["-t", "f", "--links", "+1"],
+        &["linked.txt", "alias.txt"],
+    )?;
+    run_sized(&dir, &["-t", "f", "--links", "1"], &["single.txt"])?;
+    run_sized(&dir, &["-t", "f", "--links", "-2"], &["single.txt"])?;
+    fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_links() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--links", "+x"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid --links \"+x\""));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+#[cfg(unix)]
+fn long_symlinks() -> TestResult {
+    let dir = std::env::temp_dir().join(format!("findr-long-{}", std::process::id()));
+    fs::create_dir_all(&dir)?;
+    fs::write(dir.join("target.txt"), "hello\n")?;
+    std::os::unix::fs::symlink("target.txt", dir.join("link.txt"))?;
+    std::os::unix::fs::symlink("missing.txt", dir.join("broken.txt"))?;
+    let dir = dir.display().to_string();
+
+    let cmd = Command::cargo_bin(PRG)?
+        .args([&dir, "--long", "-t", "l"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8(cmd.get_output().stdout.clone())?;
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines.iter().all(|line| line.starts_with("lrwxrwxrwx")));
+    for arrow in ["broken.txt -> missing.txt", "link.txt -> target.txt"] {
+        let arrow = format!("{}/{}", dir, arrow);
+        assert!(lines.iter().any(|line| line.ends_with(&arrow)));
+    }
+
+    Command::cargo_bin(PRG)?
+        .args([&dir, "--long", "-t", "f"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(format!(
+            r"^-rw\S+ +1 +6 {}/target.txt\n$",
+            dir
+        ))?);
+
+    fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn quit_after() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["tests/inputs", "--quit-after", "0s"])
+        .timeout(std::time::Duration::from_secs(10))
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "findr: search stopped after 0ns, results may be incomplete",
+        ));
+
+    Command::cargo_bin(PRG)?
+        .args(["tests/inputs", "--quit-after", "1h"])
+        .assert()
+        .success()
+        .stderr("");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_quit_after() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--quit-after", "10d"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid --quit-after \"10d\""));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn max_depth_1() -> TestResult {
+    run(
+        &["tests/inputs", "--max-depth", "1"],
+        "tests/expected/max_depth_1.txt",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn min_depth_1_max_depth_1() -> TestResult {
+    run(
+        &["tests/inputs", "--min-depth", "1", "--max-depth", "1"],
+        "tests/expected/min_depth_1_max_depth_1.txt",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn min_depth_3() -> TestResult {
+    run(
+        &["tests/inputs", "--min-depth", "3"],
+        "tests/expected/min_depth_3.txt",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn max_depth_0() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["tests/inputs", "--max-depth", "0"])
+        .assert()
+        .success()
+        .stdout("tests/inputs\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_depth() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--max-depth", "x"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid --max-depth \"x\""));
+
+    Command::cargo_bin(PRG)?
+        .args(["--min-depth", "2", "--max-depth", "1"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "--min-depth (2) must not be greater than --max-depth (1)",
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+#[cfg(unix)]
+fn print0() -> TestResult {
+    let dir = std::env::temp_dir().join(format!("findr-print0-{}", std::process::id()));
+    fs::create_dir_all(&dir)?;
+    let names = ["plain.txt", "with space.txt", "with\nnewline.txt"];
+    for name in names {
+        fs::write(dir.join(name), "")?;
+    }
+
+    let cmd = Command::cargo_bin(PRG)?
+        .args([dir.to_str().unwrap(), "-t", "f", "--print0"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8(cmd.get_output().stdout.clone())?;
+    fs::remove_dir_all(&dir)?;
+
+    // Every path ends with a NUL, so the last piece is empty
+    let mut paths: Vec<&str> = stdout.split('\0').collect();
+    assert_eq!(paths.pop(), Some(""));
+    paths.sort();
+    let mut expected: Vec<String> = names
+        .iter()
+        .map(|name| dir.join(name).display().to_string())
+        .collect();
+    expected.sort();
+    assert_eq!(paths, expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn name_ignore_case() -> TestResult {
+    run(
+        &["tests/inputs", "-t", "f", "-n", r"\.TXT$", "-i"],
+        "tests/expected/type_f_name_txt.txt",
+    )?;
+    run(
+        &["tests/inputs", "-t", "f", "-n", r"\.TXT$", "--ignore-case"],
+        "tests/expected/type_f_name_txt.txt",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn name_case_sensitive() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["tests/inputs", "-t", "f", "-n", r"\.TXT$"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty().trim());
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn path_pattern() -> TestResult {
+    run(
+        &["tests/inputs", "--path", r"inputs[/\\]a[/\\]b"],
+        "tests/expected/path_pattern_a_b.txt",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn path_pattern_and_name() -> TestResult {
+    // A name pattern only ever sees the last component
+    Command::cargo_bin(PRG)?
+        .args(["tests/inputs", "-n", r"a[/\\]b"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty().trim());
+
+    run(
+        &["tests/inputs", "--path", r"[/\\]a[/\\]", "-n", r"\.csv$"],
+        "tests/expected/path_a_name_csv.txt",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_path_pattern() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--path", "*"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid --path \"*\""));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn mtime_and_newer() -> TestResult {
+    let dir = std::env::temp_dir().join(format!("findr-mtime-{}", std::process::id()));
+    fs::create_dir_all(&dir)?;
+    fs::write(dir.join("new.txt"), "")?;
+    let old = File::create(dir.join("old.txt"))?;
+    old.set_modified(SystemTime::now() - Duration::from_secs(10 * 24 * 60 * 60))?;
+    drop(old);
+    let dir = dir.display().to_string();
+    let old = Path::new(&dir).join("old.txt").display().to_string();
+
+    run_sized(&dir, &["-t", "f", "--mtime", "-1"], &["new.txt"])?;
+    run_sized(&dir, &["-t", "f", "--mtime", "+5"], &["old.txt"])?;
+    run_sized(&dir, &["-t", "f", "--mtime", "10"], &["old.txt"])?;
+    run_sized(&dir, &["-t", "f", "--newer", &old], &["new.txt"])?;
+    fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_mtime_and_newer() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--mtime", "+x"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid --mtime \"+x\""));
+
+    let bad = gen_bad_file();
+    Command::cargo_bin(PRG)?
+        .args(["--newer", &bad])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(format!("--newer {}: ", bad)));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn empty() -> TestResult {
+    let dir = std::env::temp_dir().join(format!("findr-empty-{}", std::process::id()));
+    fs::create_dir_all(dir.join("empty_dir"))?;
+    fs::create_dir_all(dir.join("full_dir"))?;
+    fs::write(dir.join("empty.txt"), "")?;
+    fs::write(dir.join("full_dir").join("full.txt"), "x")?;
+    let dir = dir.display().to_string();
+
+    run_sized(&dir, &["--empty"], &["empty_dir", "empty.txt"])?;
+    run_sized(&dir, &["--empty", "-t", "d"], &["empty_dir"])?;
+    run_sized(&dir, &["--empty", "-n", r"\.txt$"], &["empty.txt"])?;
+    fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+#[cfg(unix)]
+fn follow_links() -> TestResult {
+    let dir = std::env::temp_dir().join(format!("findr-follow-{}", std::process::id()));
+    fs::create_dir_all(dir.join("real"))?;
+    fs::write(dir.join("real").join("x.txt"), "")?;
+    std::os::unix::fs::symlink(dir.join("real"), dir.join("link"))?;
+    let dir = dir.display().to_string();
+
+    // Without -L the link is listed but not entered
+    run_sized(&dir, &["-n", r"x\.txt$"], &["real/x.txt"])?;
+    run_sized(&dir, &["-t", "l"], &["link"])?;
+
+    run_sized(
+        &dir,
+        &["-L", "-n", r"x\.txt$"],
+        &["real/x.txt", "link/x.txt"],
+    )?;
+    run_sized(&dir, &["--follow", "-t", "l"], &["link"])?;
+    fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+#[cfg(unix)]
+fn follow_links_loop() -> TestResult {
+    let dir = std::env::temp_dir().join(format!("findr-loop-{}", std::process::id()));
+    fs::create_dir_all(dir.join("sub"))?;
+    std::os::unix::fs::symlink(&dir, dir.join("sub").join("back"))?;
+
+    Command::cargo_bin(PRG)?
+        .args([dir.to_str().unwrap(), "-L"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("filesystem loop back to"));
+    fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn sort_by_name() -> TestResult {
+    let cmd = Command::cargo_bin(PRG)?
+        .args(["tests/inputs/d", "tests/inputs/a", "--sort"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8(cmd.get_output().stdout.clone())?;
+    let lines: Vec<&str> = stdout.lines().collect();
+
+    let mut expected = lines.clone();
+    expected.sort();
+    assert_eq!(lines, expected);
+    assert_eq!(lines.len(), 12);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn sort_by_size() -> TestResult {
+    let dir = sized_files("sort")?;
+    let cmd = Command::cargo_bin(PRG)?
+        .args([&dir, "-t", "f", "--sort=size"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8(cmd.get_output().stdout.clone())?;
+    let lines: Vec<&str> = stdout.lines().collect();
+    let expected: Vec<String> = ["small.txt", "medium.txt", "large.txt"]
+        .iter()
+        .map(|name| Path::new(&dir).join(name).display().to_string())
+        .collect();
+    assert_eq!(lines, expected);
+    fs::remove_dir_all(&dir)?;
+    Ok(())
+}
@@ -3,11 +3,12 @@ use rand::prelude::SliceRandom;
 use rand::SeedableRng;
 use regex::{Regex, RegexBuilder};
 use std::{
+    collections::BTreeMap,
     error::Error,
     ffi::OsStr,
     fs::{self, File},
-    io::{BufRead, BufReader},
-    path::PathBuf,
+    io::{self, BufRead, BufReader},
+    path::{Path, PathBuf},
 };
 use walkdir::WalkDir;
 
@@ -18,6 +19,10 @@ pub struct Config {
     sources: Vec<String>,
     pattern: Option<Regex>,
     seed: Option<u64>,
+    stats: bool,
+    min_len: Option<usize>,
+    max_len: Option<usize>,
+    rot13: bool,
 }
 
 #[derive(Debug)]
@@ -36,7 +41,7 @@ pub fn get_args() -> MyResult<Config> {
                 .value_name("FILE")
                 .multiple(true)
                 .required(true)
-                .help("Input files or directories"),
+                .help("Input files or directories, or - for stdin"),
         )
         .arg(
             Arg::with_name("insensitive")
@@ -61,6 +66,33 @@ pub fn get_args() -> MyResult<Config> {
                 .takes_value(true)
                 .help("Random seed"),
         )
+        .arg(
+            Arg::with_name("stats")
+                .long("stats")
+                .takes_value(false)
+                .help("Print the number of fortunes per source"),
+        )
+        .arg(
+            Arg::with_name("min_len")
+                .value_name("LENGTH")
+                .long("min-len")
+                .takes_value(true)
+                .help("Minimum fortune length in characters"),
+        )
+        .arg(
+            Arg::with_name("max_len")
+                .value_name("LENGTH")
+                .long("max-len")
+                .takes_value(true)
+                .help("Maximum fortune length in characters"),
+        )
+        .arg(
+            Arg::with_name("rot13")
+                .short("o")
+                .long("rot13")
+                .takes_value(false)
+                .help("Decode ROT13-encoded fortunes"),
+        )
         .get_matches();
 
     let sources = matches.values_of_lossy("files").unwrap();
@@ -74,11 +106,22 @@ pub fn get_args() -> MyResult<Config> {
         })
         .transpose()?;
     let seed = matches.value_of("seed").map(parse_u64).transpose()?;
+    let min_len = matches.value_of("min_len").map(parse_usize).transpose()?;
+    let max_len = matches.value_of("max_len").map(parse_usize).transpose()?;
+    if let (Some(min), Some(max)) = (min_len, max_len) {
+        if min > max {
+            return Err(format!("--min-len {} is greater than --max-len {}", min, max).into());
+        }
+    }
 
     Ok(Config {
         sources,
         pattern,
         seed,
+        stats: matches.is_present("stats"),
+        min_len,
+        max_len,
+        rot13: matches.is_present("rot13"),
     })
 }
 
@@ -87,11 +130,21 @@ fn parse_u64(val: &str) -> MyResult<u64> {
         .map_err(|_| format!("\"{}\" not a valid integer", val).into())
 }
 
+fn parse_usize(val: &str) -> MyResult<usize> {
+    val.parse()
+        .map_err(|_| format!("\"{}\" not a valid length", val).into())
+}
+
 fn find_files(paths: &[String]) -> MyResult<Vec<PathBuf>> {
     let dat = OsStr::new("dat");
     let mut files = Vec::new();
 
     for path in paths {
+        if path == "-" {
+            files.push(PathBuf::from(path));
+            continue;
+        }
+
         match fs::metadata(path) {
             Ok(_) => files.extend(
                 WalkDir::new(path)
@@ -109,20 +162,30 @@ fn find_files(paths: &[String]) -> MyResult<Vec<PathBuf>> {
     Ok(files)
 }
 
-fn read_fortunes(paths: &[PathBuf]) -> MyResult<Vec<Fortune>> {
+fn open(path: &Path) -> MyResult<Box<dyn BufRead>> {
+    if path == Path::new("-") {
+        Ok(Box::new(BufReader::new(io::stdin())))
+    } else {
+        let file = File::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Ok(Box::new(BufReader::new(file)))
+    }
+}
+
+/// Read the fortunes in each file, decoding them from ROT13 with `rot13`
+#[allow(clippy::lines_filter_map_ok)]
+fn read_fortunes(paths: &[PathBuf], rot13: bool) -> MyResult<Vec<Fortune>> {
     let mut fortunes = vec![];
     let mut buffer = vec![];
 
     for path in paths {
         let basename = path.file_name().unwrap().to_string_lossy().into_owned();
-        let file = File::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
-
-        for line in BufReader::new(file).lines().filter_map(Result::ok) {
+        for line in open(path)?.lines().filter_map(Result::ok) {
             if line == "%" {
                 if !buffer.is_empty() {
+                    let text = buffer.join("\n");
                     fortunes.push(Fortune {
                         source: basename.clone(),
-                        text: buffer.join("\n"),
+                        text: if rot13 { rot13_text(&text) } else { text },
                     });
                     buffer.clear();
                 }
@@ -134,6 +197,18 @@ fn read_fortunes(paths: &[PathBuf]) -> MyResult<Vec<Fortune>> {
     Ok(fortunes)
 }
 
+/// Rotate ASCII letters 13 places, which both encodes and decodes ROT13,
+/// leaving everything else as it is
+fn rot13_text(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            'a'..='m' | 'A'..='M' => (c as u8 + 13) as char,
+            'n'..='z' | 'N'..='Z' => (c as u8 - 13) as char,
+            _ => c,
+        })
+        .collect()
+}
+
 fn pick_fortune(fortunes: &[Fortune], seed: Option<u64>) -> Option<String> {
     match seed {
         Some(s) => {
@@ -151,16 +226,36 @@ fn pick_fortune(fortunes: &[Fortune], seed: Option<u64>) -> Option<String> {
     }
 }
 
+fn count_sources(fortunes: &[Fortune]) -> BTreeMap<&str, usize> {
+    let mut counts = BTreeMap::new();
+    for fortune in fortunes {
+        *counts.entry(fortune.source.as_str()).or_insert(0) += 1;
+    }
+    counts
+}
+
 pub fn run(config: Config) -> MyResult<()> {
     let files = find_files(&config.sources)?;
-    let fortunes = read_fortunes(&files)?;
-    if let Some(pattern) = config.pattern {
+    let fortunes: Vec<_> = read_fortunes(&files, config.rot13)?
+        .into_iter()
+        .filter(|fortune| {
+            let len = fortune.text.chars().count();
+            config.min_len.is_none_or(|min| len >= min)
+                && config.max_len.is_none_or(|max| len <= max)
+        })
+        .collect();
+    if config.stats {
+        for (source, count) in count_sources(&fortunes) {
+            println!("{:>6} {}", count, source);
+        }
+        println!("{:>6} total", fortunes.len());
+    } else if let Some(pattern) = config.pattern {
         let mut prev_source = None;
         for fortune in fortunes
             .iter()
             .filter(|fortune| pattern.is_match(&fortune.text))
         {
-            if prev_source.as_ref().map_or(true, |s| s != &fortune.source) {
+            if prev_source.as_ref() != Some(&fortune.source) {
                 eprintln!("({})\n%", fortune.source);
                 prev_source = Some(fortune.source.clone());
             }
@@ -179,7 +274,9 @@ pub fn run(config: Config) -> MyResult<()> {
 
 #[cfg(test)]
 mod tests {
-    use super::{find_files, parse_u64, pick_fortune, read_fortunes, Fortune};
+    use super::{
+        count_sources, find_files, parse_u64, pick_fortune, read_fortunes, rot13_text, Fortune,
+    };
     use std::path::PathBuf;
 
     #[test]
@@ -206,10 +303,17 @@ mod tests {
         let files = res.unwrap();
         assert_eq!(files.len(), 1);
         assert_eq!(
-            files.get(0).unwrap().to_string_lossy(),
+            files.first().unwrap().to_string_lossy(),
             "./tests/inputs/jokes"
         );
 
+        // Passes stdin through without touching the filesystem
+        let res = find_files(&["-".to_string(), "./tests/inputs/jokes".to_string()]);
+        assert!(res.is_ok());
+        let files = res.unwrap();
+        assert_eq!(files.len(), 2);
+        assert!(files.contains(&PathBuf::from("-")));
+
         // Fails to find a bad file
         let res = find_files(&["/path/does/not/exist".to_string()]);
         assert!(res.is_err());
@@ -221,7 +325,7 @@ mod tests {
         // Check number and order of files
         let files = res.unwrap();
         assert_eq!(files.len(), 5);
-        let first = files.get(0).unwrap().display().to_string();
+        let first = files.first().unwrap().display().to_string();
         assert!(first.contains("ascii-art"));
         let last = files.last().unwrap().display().to_string();
         assert!(last.contains("quotes"));
@@ -246,7 +350,7 @@ mod tests {
     #[test]
     fn test_read_fortunes() {
         // One input file
-        let res = read_fortunes(&[PathBuf::from("./tests/inputs/jokes")]);
+        let res = read_fortunes(&[PathBuf::from("./tests/inputs/jokes")], false);
         assert!(res.is_ok());
 
         if let Ok(fortunes) = res {
@@ -265,14 +369,61 @@ mod tests {
         }
 
         // Multiple input files
-        let res = read_fortunes(&[
-            PathBuf::from("./tests/inputs/jokes"),
-            PathBuf::from("./tests/inputs/quotes"),
-        ]);
+        let res = read_fortunes(
+            &[
+                PathBuf::from("./tests/inputs/jokes"),
+                PathBuf::from("./tests/inputs/quotes"),
+            ],
+            false,
+        );
         assert!(res.is_ok());
         assert_eq!(res.unwrap().len(), 11);
     }
 
+    #[test]
+    fn test_rot13() {
+        assert_eq!(rot13_text("Uryyb, Jbeyq!"), "Hello, World!");
+        assert_eq!(rot13_text("abcxyz ABCXYZ"), "nopklm NOPKLM");
+        // Digits, punctuation and non-ASCII letters are untouched
+        assert_eq!(rot13_text("123 & é\n%"), "123 & é\n%");
+        assert_eq!(rot13_text(&rot13_text("Round trip")), "Round trip");
+    }
+
+    #[test]
+    fn test_read_fortunes_rot13() {
+        let riddles = [PathBuf::from("./tests/off/riddles")];
+        let fortunes = read_fortunes(&riddles, true).unwrap();
+        assert_eq!(fortunes.len(), 2);
+        assert_eq!(
+            fortunes[0].text,
+            "Q: Why did the chicken cross the road?\n\
+            A: To get to the other side (123 steps, 4 lanes)."
+        );
+
+        // Left encoded without the flag
+        let encoded = read_fortunes(&riddles, false).unwrap();
+        assert_eq!(encoded[0].text, rot13_text(&fortunes[0].text));
+
+        let jokes = [PathBuf::from("./tests/inputs/jokes")];
+        let plain = read_fortunes(&jokes, false).unwrap();
+        let decoded = read_fortunes(&jokes, true).unwrap();
+        assert_eq!(decoded[0].text, rot13_text(&plain[0].text));
+    }
+
+    #[test]
+    fn test_count_sources() {
+        let fortunes = read_fortunes(
+            &[
+                PathBuf::from("./tests/inputs/quotes"),
+                PathBuf::from("./tests/inputs/jokes"),
+            ],
+            false,
+        )
+        .unwrap();
+        let counts: Vec<_> = count_sources(&fortunes).into_iter().collect();
+        assert_eq!(counts, [("jokes", 6), ("quotes", 5)]);
+    }
+
     #[test]
     fn test_pick_fortune() {
         // Create a slice of fortunes
@@ -37,7 +37,7 @@ fn dies_bad_file() -> TestResult {
     let bad = gen_bad_file();
     let expected = format!("{}: .* [(]os error 2[)]", bad);
     Command::cargo_bin(PRG)?
-        .args(&[LITERATURE, &bad])
+        .args([LITERATURE, &bad])
         .assert()
         .failure()
         .stderr(predicate::str::is_match(expected)?);
@@ -50,7 +50,7 @@ fn dies_bad_seed() -> TestResult {
     let bad = random_string();
     let expected = format!("\"{}\" not a valid integer", &bad);
     Command::cargo_bin(PRG)?
-        .args(&[LITERATURE, "--seed", &bad])
+        .args([LITERATURE, "--seed", &bad])
         .assert()
         .failure()
         .stderr(predicate::str::contains(expected));
@@ -101,6 +101,60 @@ fn dir_seed_10() -> TestResult {
     )
 }
 
+// --------------------------------------------------
+#[test]
+fn stdin_and_file() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["-", JOKES, "-m", "piped|lichen"])
+        .write_stdin("A piped fortune.\n%\n")
+        .assert()
+        .success()
+        .stderr("(jokes)\n%\n(-)\n%\n")
+        .stdout(
+            "Q: Why did the fungus and the alga marry?\n\
+            A: Because they took a lichen to each other!\n%\n\
+            A piped fortune.\n%\n",
+        );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn stats() -> TestResult {
+    run(
+        &["--stats", FORTUNE_DIR],
+        "     4 ascii-art\n     6 jokes\n     4 literature\n     5 quotes\n    19 total\n",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn length_band() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args([QUOTES, "--min-len", "50", "--max-len", "60", "-m", "Berra"])
+        .assert()
+        .success()
+        .stdout("You can observe a lot just by watching.\n-- Yogi Berra\n%\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn length_band_empty() -> TestResult {
+    run(&[QUOTES, "--max-len", "10"], "No fortunes found\n")
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_min_len_over_max_len() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args([QUOTES, "--min-len", "20", "--max-len", "10"])
+        .assert()
+        .failure()
+        .stderr("--min-len 20 is greater than --max-len 10\n");
+    Ok(())
+}
+
 // --------------------------------------------------
 fn run_outfiles(args: &[&str], out_file: &str, err_file: &str) -> TestResult {
     let out = fs::read_to_string(out_file)?;
@@ -173,3 +227,33 @@ fn mark_twain_lower_i() -> TestResult {
         "tests/expected/twain_lower_i.err",
     )
 }
+
+// --------------------------------------------------
+#[test]
+fn rot13() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--rot13", "-m", "sticky", "tests/off"])
+        .assert()
+        .success()
+        .stdout("Q: What's brown & sticky?\nA: A stick!\n%\n")
+        .stderr("(riddles)\n%\n");
+    Command::cargo_bin(PRG)?
+        .args(["-m", "sticky", "tests/off"])
+        .assert()
+        .success()
+        .stdout("");
+
+    Command::cargo_bin(PRG)?
+        .args(["--rot13", "-m", "sticky", "-"])
+        .write_stdin("D: Jung'f oebja & fgvpxl?\nN: N fgvpx!\n%\n")
+        .assert()
+        .success()
+        .stdout("Q: What's brown & sticky?\nA: A stick!\n%\n");
+    Command::cargo_bin(PRG)?
+        .args(["-m", "sticky", "-"])
+        .write_stdin("D: Jung'f oebja & fgvpxl?\nN: N fgvpx!\n%\n")
+        .assert()
+        .success()
+        .stdout("");
+    Ok(())
+}
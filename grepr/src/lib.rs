@@ -1,10 +1,19 @@
 use clap::{App, Arg};
 use regex::{Regex, RegexBuilder};
 use std::{
+    collections::VecDeque,
     error::Error,
     fs::{self, File},
-    io::{self, BufRead, BufReader},
+    io::{self, BufRead, BufReader, Read, Write},
     mem,
+    num::NonZeroUsize,
+    str,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc,
+    },
+    thread,
+    time::{Duration, Instant},
 };
 use walkdir::WalkDir;
 
@@ -16,7 +25,46 @@ pub struct Config {
     files: Vec<String>,
     recursive: bool,
     count: bool,
+    count_matches: bool,
+    include_zero: bool,
     invert_match: bool,
+    byte_offset: bool,
+    before_context: usize,
+    after_context: usize,
+    vimgrep: bool,
+    with_filename: Option<bool>,
+    text: bool,
+    threads: usize,
+    sort: Option<SortBy>,
+    sort_reverse: bool,
+    debug: bool,
+    multiline: bool,
+    max_buffer: u64,
+}
+
+/// How much of a file `--multiline` reads at once unless told otherwise
+const DEFAULT_MAX_BUFFER: u64 = 64 * 1024 * 1024;
+
+/// The order in which a recursive search visits files
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SortBy {
+    Path,
+    Modified,
+}
+
+/// Which notion of a word character `-w` uses at the edges of a match
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WordBoundary {
+    Unicode,
+    Ascii,
+}
+
+/// What searching one input produced, held back so that results from
+/// several threads can be printed in input order
+#[derive(Debug, Default)]
+struct Searched {
+    out: String,
+    err: String,
 }
 
 pub fn get_args() -> MyResult<Config> {
@@ -27,7 +75,7 @@ pub fn get_args() -> MyResult<Config> {
         .arg(
             Arg::with_name("pattern")
                 .value_name("PATTERN")
-                .required(true)
+                .required_unless_one(&["pattern_files", "regexps"])
                 .help("Search pattern"),
         )
         .arg(
@@ -44,6 +92,26 @@ pub fn get_args() -> MyResult<Config> {
                 .takes_value(false)
                 .help("Count occurences"),
         )
+        .arg(
+            Arg::with_name("count_matches")
+                .long("count-matches")
+                .takes_value(false)
+                .conflicts_with("invert")
+                .help("Count every match rather than matching lines"),
+        )
+        .arg(
+            Arg::with_name("count_only_nonzero")
+                .long("count-only-nonzero")
+                .takes_value(false)
+                .conflicts_with("include_zero")
+                .help("Only print counts for files with matches"),
+        )
+        .arg(
+            Arg::with_name("include_zero")
+                .long("include-zero")
+                .takes_value(false)
+                .help("Print zero counts in recursive mode"),
+        )
         .arg(
             Arg::with_name("insensitive")
                 .short("i")
@@ -51,6 +119,26 @@ pub fn get_args() -> MyResult<Config> {
                 .takes_value(false)
                 .help("Case-insensitive"),
         )
+        .arg(
+            Arg::with_name("word_regexp")
+                .short("w")
+                .long("word-regexp")
+                .takes_value(false)
+                .help("Only match whole words"),
+        )
+        .arg(
+            Arg::with_name("word_boundary_unicode")
+                .long("word-boundary-unicode")
+                .takes_value(false)
+                .help("Treat any Unicode letter or digit as part of a word for -w (default)"),
+        )
+        .arg(
+            Arg::with_name("ascii_boundaries")
+                .long("ascii-boundaries")
+                .takes_value(false)
+                .conflicts_with("word_boundary_unicode")
+                .help("Only treat ASCII letters, digits and _ as part of a word for -w"),
+        )
         .arg(
             Arg::with_name("invert")
                 .short("v")
@@ -65,55 +153,659 @@ pub fn get_args() -> MyResult<Config> {
                 .takes_value(false)
                 .help("Recursive search"),
         )
+        .arg(
+            Arg::with_name("regexps")
+                .value_name("PATTERN")
+                .short("e")
+                .long("regexp")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .allow_hyphen_values(true)
+                .help("Use PATTERN for matching; may be repeated"),
+        )
+        .arg(
+            Arg::with_name("pattern_files")
+                .value_name("FILE")
+                .short("f")
+                .long("file")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .help("Read patterns from FILE, one per line (- for stdin)"),
+        )
+        .arg(
+            Arg::with_name("byte_offset")
+                .short("b")
+                .long("byte-offset")
+                .takes_value(false)
+                .conflicts_with_all(&["vimgrep", "after_context", "before_context", "context"])
+                .help("Print the byte offset of each matching line"),
+        )
+        .arg(
+            Arg::with_name("after_context")
+                .value_name("NUM")
+                .short("A")
+                .long("after-context")
+                .takes_value(true)
+                .help("Print NUM lines of trailing context"),
+        )
+        .arg(
+            Arg::with_name("before_context")
+                .value_name("NUM")
+                .short("B")
+                .long("before-context")
+                .takes_value(true)
+                .help("Print NUM lines of leading context"),
+        )
+        .arg(
+            Arg::with_name("context")
+                .value_name("NUM")
+                .short("C")
+                .long("context")
+                .takes_value(true)
+                .help("Print NUM lines of output context"),
+        )
+        .arg(
+            Arg::with_name("vimgrep")
+                .long("vimgrep")
+                .takes_value(false)
+                .conflicts_with_all(&["count", "count_matches", "invert"])
+                .help("Print file:line:column:text for every match"),
+        )
+        .arg(
+            Arg::with_name("with_filename")
+                .short("H")
+                .long("with-filename")
+                .takes_value(false)
+                .help("Print the file name for each match"),
+        )
+        .arg(
+            Arg::with_name("text")
+                .short("a")
+                .long("text")
+                .alias("include-binary")
+                .takes_value(false)
+                .help("Search binary files as if they were text"),
+        )
+        .arg(
+            Arg::with_name("no_filename")
+                .long("no-filename")
+                .takes_value(false)
+                .conflicts_with("with_filename")
+                .help("Never print file names"),
+        )
+        .arg(
+            Arg::with_name("threads")
+                .value_name("NUM")
+                .short("j")
+                .long("threads")
+                .takes_value(true)
+                .help("Search NUM files at a time [default: number of CPUs]"),
+        )
+        .arg(
+            Arg::with_name("sort")
+                .value_name("BY")
+                .long("sort")
+                .takes_value(true)
+                .possible_values(&["path", "modified"])
+                .help("Search the files of a recursive search by path or oldest first"),
+        )
+        .arg(
+            Arg::with_name("sort_files")
+                .long("sort-files")
+                .takes_value(false)
+                .conflicts_with("sort")
+                .help("Same as --sort=path"),
+        )
+        .arg(
+            Arg::with_name("sort_reverse")
+                .long("sort-reverse")
+                .takes_value(false)
+                .help("Reverse the --sort order (path order if none is given)"),
+        )
+        .arg(
+            Arg::with_name("debug")
+                .long("debug")
+                .takes_value(false)
+                .help("Print how long each file took to search to stderr"),
+        )
+        .arg(
+            Arg::with_name("multiline")
+                .short("U")
+                .long("multiline")
+                .takes_value(false)
+                .conflicts_with_all(&[
+                    "invert",
+                    "vimgrep",
+                    "after_context",
+                    "before_context",
+                    "context",
+                ])
+                .help("Let matches span lines, printing every line they touch"),
+        )
+        .arg(
+            Arg::with_name("dotall")
+                .long("dotall")
+                .alias("multiline-dotall")
+                .takes_value(false)
+                .requires("multiline")
+                .help("Let . match newlines in --multiline mode"),
+        )
+        .arg(
+            Arg::with_name("max_buffer")
+                .value_name("SIZE")
+                .long("max-buffer")
+                .takes_value(true)
+                .requires("multiline")
+                .help("Search files larger than SIZE, e.g. 512K, line by line [default: 64M]"),
+        )
         .get_matches();
 
-    let pattern = matches.value_of("pattern").unwrap();
-    let pattern = RegexBuilder::new(pattern)
-        .case_insensitive(matches.is_present("insensitive"))
-        .build()
-        .map_err(|_| format!("Invalid pattern \"{}\"", pattern))?;
+    let mut files = if matches.occurrences_of("files") > 0 {
+        matches.values_of_lossy("files").unwrap()
+    } else {
+        vec![]
+    };
+    let pattern_files = matches.values_of_lossy("pattern_files");
+    let regexps = matches.values_of_lossy("regexps");
+    let mut patterns = regexps.clone().unwrap_or_default();
+    if let Some(pattern) = matches.value_of("pattern") {
+        // With -e or -f, the first positional is a file rather than a pattern
+        if pattern_files.is_some() || regexps.is_some() {
+            files.insert(0, pattern.to_string());
+        } else {
+            patterns.push(pattern.to_string());
+        }
+    }
+    if files.is_empty() {
+        files.push("-".to_string());
+    }
+    if let Some(pattern_files) = pattern_files {
+        if pattern_files.iter().any(|f| f == "-") && files.iter().any(|f| f == "-") {
+            return Err(From::from("Cannot read both patterns and input from stdin"));
+        }
+        patterns.extend(read_patterns(&pattern_files)?);
+    }
+    let words = match (
+        matches.is_present("word_regexp"),
+        matches.is_present("ascii_boundaries"),
+    ) {
+        (false, _) => None,
+        (true, false) => Some(WordBoundary::Unicode),
+        (true, true) => Some(WordBoundary::Ascii),
+    };
+    let pattern = build_pattern(
+        &patterns,
+        matches.is_present("insensitive"),
+        words,
+        matches.is_present("dotall"),
+    )?;
+
+    let context = matches.value_of("context").map(parse_usize).transpose()?;
+    let before_context = matches
+        .value_of("before_context")
+        .map(parse_usize)
+        .transpose()?
+        .or(context)
+        .unwrap_or(0);
+    let after_context = matches
+        .value_of("after_context")
+        .map(parse_usize)
+        .transpose()?
+        .or(context)
+        .unwrap_or(0);
+
+    let threads = match matches.value_of("threads") {
+        Some(val) => val
+            .parse::<NonZeroUsize>()
+            .map_err(|_| format!("\"{}\" not a valid number of threads", val))?,
+        None => thread::available_parallelism().unwrap_or(NonZeroUsize::MIN),
+    };
+
+    let max_buffer = matches
+        .value_of("max_buffer")
+        .map(|val| parse_size(val).map_err(|_| format!("\"{}\" not a valid size", val)))
+        .transpose()?
+        .unwrap_or(DEFAULT_MAX_BUFFER);
+
+    // Like ripgrep, a recursive count leaves out files without matches
+    let recursive = matches.is_present("recursive");
+    let include_zero = if matches.is_present("count_only_nonzero") {
+        false
+    } else {
+        matches.is_present("include_zero") || !recursive
+    };
 
     Ok(Config {
         pattern,
-        files: matches.values_of_lossy("files").unwrap(),
-        recursive: matches.is_present("recursive"),
+        files,
+        recursive,
         count: matches.is_present("count"),
+        count_matches: matches.is_present("count_matches"),
+        include_zero,
         invert_match: matches.is_present("invert"),
+        byte_offset: matches.is_present("byte_offset"),
+        before_context,
+        after_context,
+        vimgrep: matches.is_present("vimgrep"),
+        with_filename: if matches.is_present("with_filename") {
+            Some(true)
+        } else if matches.is_present("no_filename") {
+            Some(false)
+        } else {
+            None
+        },
+        text: matches.is_present("text"),
+        threads: threads.get(),
+        sort: match matches.value_of("sort") {
+            Some("modified") => Some(SortBy::Modified),
+            Some(_) => Some(SortBy::Path),
+            None if matches.is_present("sort_files") || matches.is_present("sort_reverse") => {
+                Some(SortBy::Path)
+            }
+            None => None,
+        },
+        sort_reverse: matches.is_present("sort_reverse"),
+        debug: matches.is_present("debug"),
+        multiline: matches.is_present("multiline"),
+        max_buffer,
     })
 }
 
+fn read_patterns(filenames: &[String]) -> MyResult<Vec<String>> {
+    let mut patterns = vec![];
+    for filename in filenames {
+        let file = open(filename).map_err(|e| format!("{}: {}", filename, e))?;
+        for line in file.lines() {
+            let line = line?;
+            patterns.push(line.trim_end_matches('\r').to_string());
+        }
+    }
+    Ok(patterns)
+}
+
+/// Compile the patterns into one regex that matches if any of them does,
+/// and with `words` only where the match starts and ends a word. With
+/// `dotall`, `.` also matches a newline.
+fn build_pattern(
+    patterns: &[String],
+    insensitive: bool,
+    words: Option<WordBoundary>,
+    dotall: bool,
+) -> MyResult<Regex> {
+    for pattern in patterns {
+        Regex::new(pattern).map_err(|_| format!("Invalid pattern \"{}\"", pattern))?;
+    }
+    let combined = match patterns {
+        [] => return Err(From::from("No patterns given")),
+        [pattern] => pattern.to_string(),
+        _ => patterns
+            .iter()
+            .map(|pattern| format!("(?:{})", pattern))
+            .collect::<Vec<_>>()
+            .join("|"),
+    };
+    let combined = match words {
+        None => combined,
+        Some(WordBoundary::Unicode) => format!(r"\b(?:{})\b", combined),
+        Some(WordBoundary::Ascii) => format!(r"(?-u:\b)(?:{})(?-u:\b)", combined),
+    };
+    RegexBuilder::new(&combined)
+        .case_insensitive(insensitive)
+        .dot_matches_new_line(dotall)
+        .build()
+        .map_err(|_| format!("Invalid pattern \"{}\"", combined).into())
+}
+
+fn parse_usize(val: &str) -> MyResult<usize> {
+    val.parse()
+        .map_err(|_| format!("\"{}\" not a valid context length", val).into())
+}
+
+/// The --max-buffer size, read the way findr reads --size: a number of
+/// bytes with an optional B, K, M or G suffix in 1024-based units
+fn parse_size(val: &str) -> MyResult<u64> {
+    let (num, unit) = match val.char_indices().last() {
+        Some((i, c)) if c.is_ascii_alphabetic() => (&val[..i], c.to_ascii_uppercase()),
+        _ => (val, 'B'),
+    };
+    let multiplier = match unit {
+        'B' => 1,
+        'K' => 1024,
+        'M' => 1024 * 1024,
+        'G' => 1024 * 1024 * 1024,
+        _ => return Err(From::from(val)),
+    };
+    match num.parse::<u64>() {
+        Ok(n) if num.bytes().all(|b| b.is_ascii_digit()) => {
+            n.checked_mul(multiplier).ok_or_else(|| From::from(val))
+        }
+        _ => Err(From::from(val)),
+    }
+}
+
 pub fn run(config: Config) -> MyResult<()> {
-    let entries = find_files(&config.files, config.recursive);
-    let num_files = entries.len();
-    let print = |fname: &str, val: &str| {
-        if num_files > 1 {
-            print!("{}:{}", fname, val);
-        } else {
-            print!("{}", val);
+    // Errors become messages so the entries can be shared with the workers
+    let entries: Vec<Result<String, String>> = find_files(
+        &config.files,
+        config.recursive,
+        config.sort,
+        config.sort_reverse,
+    )
+    .into_iter()
+    .map(|entry| entry.map_err(|e| e.to_string()))
+    .collect();
+    // Names are shown for several inputs unless -H or --no-filename says
+    // otherwise, but stdin alone has no name worth showing even with -H
+    let with_filename = match config.with_filename {
+        _ if config.files == ["-"] => false,
+        Some(flag) => flag,
+        None => entries.len() > 1,
+    };
+    let with_context = !config.count
+        && !config.count_matches
+        && (config.before_context > 0 || config.after_context > 0);
+
+    // Workers take the next entry as they free up, and the results are
+    // printed here in entry order no matter which finishes first
+    let next = AtomicUsize::new(0);
+    let (tx, rx) = mpsc::channel();
+    thread::scope(|scope| {
+        for _ in 0..config.threads.min(entries.len()) {
+            let tx = tx.clone();
+            let (config, entries, next) = (&config, &entries, &next);
+            scope.spawn(move || loop {
+                let index = next.fetch_add(1, Ordering::SeqCst);
+                let searched = match entries.get(index) {
+                    None => break,
+                    Some(Err(e)) => Searched {
+                        err: format!("{}\n", e),
+                        ..Default::default()
+                    },
+                    Some(Ok(filename)) => {
+                        let start = Instant::now();
+                        let mut searched = search(config, filename, with_filename, with_context);
+                        if config.debug {
+                            searched.err += &debug_stats(filename, start.elapsed());
+                        }
+                        searched
+                    }
+                };
+                if tx.send((index, searched)).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(tx);
+
+        let mut pending = vec![];
+        pending.resize_with(entries.len(), || None);
+        let mut printed = 0;
+        let mut need_separator = false;
+        for (index, searched) in rx {
+            pending[index] = Some(searched);
+            while let Some(Some(searched)) = pending.get_mut(printed).map(Option::take) {
+                if !searched.out.is_empty() {
+                    let mut stdout = io::stdout().lock();
+                    if with_context && need_separator {
+                        writeln!(stdout, "--")?;
+                    }
+                    stdout.write_all(searched.out.as_bytes())?;
+                    need_separator = true;
+                }
+                eprint!("{}", searched.err);
+                printed += 1;
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Describe how long a file took to search and, when its size is known,
+/// the throughput
+fn debug_stats(filename: &str, elapsed: Duration) -> String {
+    let name = display_name(filename);
+    match fs::metadata(filename) {
+        Ok(meta) if filename != "-" && meta.is_file() => {
+            let rate = meta.len() as f64 / elapsed.as_secs_f64().max(f64::EPSILON);
+            format!(
+                "grepr: debug: {}: {} bytes in {:?} ({:.0} bytes/sec)\n",
+                name,
+                meta.len(),
+                elapsed,
+                rate
+            )
         }
+        _ => format!("grepr: debug: {}: searched in {:?}\n", name, elapsed),
+    }
+}
+
+/// Search one file, collecting what would be printed for it. The members
+/// of a tar archive are each searched in turn and named `archive.tar:member`.
+fn search(config: &Config, filename: &str, with_filename: bool, with_context: bool) -> Searched {
+    if filename != "-" && filename.ends_with(".tar") {
+        return search_archive(config, filename, with_context);
+    }
+    match open(filename) {
+        Err(e) => Searched {
+            err: format!("{}: {}\n", filename, e),
+            ..Default::default()
+        },
+        Ok(file) => search_reader(config, file, filename, with_filename, with_context),
+    }
+}
+
+/// Search the regular files inside a tar archive, always prefixing
+/// matches with the archive and member names
+fn search_archive(config: &Config, filename: &str, with_context: bool) -> Searched {
+    let mut searched = Searched::default();
+    let mut archive = match File::open(filename) {
+        Err(e) => {
+            searched.err = format!("{}: {}\n", filename, e);
+            return searched;
+        }
+        Ok(file) => tar::Archive::new(file),
     };
+    let entries = match archive.entries() {
+        Err(e) => {
+            searched.err = format!("{}: {}\n", filename, e);
+            return searched;
+        }
+        Ok(entries) => entries,
+    };
+
     for entry in entries {
-        match entry {
-            Err(e) => eprintln!("{}", e),
-            Ok(filename) => match open(&filename) {
-                Err(e) => eprintln!("{}: {}", filename, e),
-                Ok(file) => match find_lines(file, &config.pattern, config.invert_match) {
-                    Err(e) => eprintln!("{}", e),
-                    Ok(matches) => {
-                        if config.count {
-                            print(&filename, &format!("{}\n", matches.len()));
-                        } else {
-                            for line in &matches {
-                                print(&filename, line);
-                            }
+        let entry = match entry {
+            Err(e) => {
+                searched.err += &format!("{}: {}\n", filename, e);
+                break;
+            }
+            Ok(entry) => entry,
+        };
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let name = match entry.path() {
+            Ok(member) => format!("{}:{}", filename, member.display()),
+            Err(e) => {
+                searched.err += &format!("{}: {}\n", filename, e);
+                continue;
+            }
+        };
+        let member = search_reader(config, BufReader::new(entry), &name, true, with_context);
+        if with_context && !searched.out.is_empty() && !member.out.is_empty() {
+            searched.out += "--\n";
+        }
+        searched.out += &member.out;
+        searched.err += &member.err;
+    }
+    searched
+}
+
+/// Search an opened input, collecting what would be printed for it
+fn search_reader<T: BufRead>(
+    config: &Config,
+    mut file: T,
+    filename: &str,
+    with_filename: bool,
+    with_context: bool,
+) -> Searched {
+    let mut searched = Searched::default();
+    let prefix = |sep: char| {
+        if with_filename {
+            format!("{}{}", display_name(filename), sep)
+        } else {
+            String::new()
+        }
+    };
+    // Like GNU grep, a binary file only says whether it matches unless
+    // counting, which works the same for any input
+    let binary = !config.text && is_binary(&mut file);
+    let binary_matches = || format!("Binary file {} matches\n", display_name(filename));
+
+    if config.vimgrep {
+        match find_columns(file, &config.pattern) {
+            Err(e) => searched.err = format!("{}\n", e),
+            Ok(matches) if binary => {
+                if !matches.is_empty() {
+                    searched.out = binary_matches();
+                }
+            }
+            Ok(matches) => {
+                for (line_num, col, line) in &matches {
+                    searched.out +=
+                        &format!("{}:{}:{}:{}\n", display_name(filename), line_num, col, line);
+                }
+            }
+        }
+    } else if config.count_matches {
+        let counted = if config.multiline {
+            match read_whole(file, config, filename, &mut searched.err) {
+                Err(e) => Err(e),
+                Ok(Ok(text)) => Ok(config.pattern.find_iter(&text).count()),
+                Ok(Err(rest)) => count_matches(rest, &config.pattern),
+            }
+        } else {
+            count_matches(file, &config.pattern)
+        };
+        match counted {
+            Err(e) => searched.err = format!("{}\n", e),
+            Ok(count) => {
+                if config.include_zero || count > 0 {
+                    searched.out = format!("{}{}\n", prefix(':'), count);
+                }
+            }
+        }
+    } else if with_context {
+        match find_context(
+            file,
+            &config.pattern,
+            config.invert_match,
+            config.before_context,
+            config.after_context,
+        ) {
+            Err(e) => searched.err = format!("{}\n", e),
+            Ok(lines) if binary => {
+                if lines.iter().any(|(_, is_match, _)| *is_match) {
+                    searched.out = binary_matches();
+                }
+            }
+            Ok(lines) => {
+                let mut prev_index = None;
+                for (index, is_match, line) in &lines {
+                    if prev_index.is_some_and(|prev| prev + 1 != *index) {
+                        searched.out += "--\n";
+                    }
+                    let sep = if *is_match { ':' } else { '-' };
+                    searched.out += &format!("{}{}", prefix(sep), line);
+                    prev_index = Some(*index);
+                }
+            }
+        }
+    } else {
+        let found = if config.multiline {
+            match read_whole(file, config, filename, &mut searched.err) {
+                Err(e) => Err(e),
+                Ok(Ok(text)) => Ok(find_multiline(&text, &config.pattern)),
+                Ok(Err(rest)) => find_lines(rest, &config.pattern, config.invert_match),
+            }
+        } else {
+            find_lines(file, &config.pattern, config.invert_match)
+        };
+        match found {
+            Err(e) => searched.err = format!("{}\n", e),
+            Ok(matches) => {
+                if config.count {
+                    if config.include_zero || !matches.is_empty() {
+                        searched.out = format!("{}{}\n", prefix(':'), matches.len());
+                    }
+                } else if binary {
+                    if !matches.is_empty() {
+                        searched.out = binary_matches();
+                    }
+                } else {
+                    for (offset, line) in &matches {
+                        searched.out += &prefix(':');
+                        if config.byte_offset {
+                            searched.out += &format!("{}:", offset);
                         }
+                        searched.out += line;
                     }
-                },
-            },
+                }
+            }
         }
     }
+    searched
+}
+
+/// Read a whole input for --multiline. One larger than --max-buffer is
+/// instead handed back as a reader to search line by line, noting so in
+/// `err`.
+fn read_whole<'a, T: BufRead + 'a>(
+    mut file: T,
+    config: &Config,
+    filename: &str,
+    err: &mut String,
+) -> MyResult<Result<String, Box<dyn BufRead + 'a>>> {
+    // Read one byte past the limit to tell whether the file fits
+    let mut buffer = vec![];
+    file.by_ref()
+        .take(config.max_buffer + 1)
+        .read_to_end(&mut buffer)?;
+    if buffer.len() as u64 > config.max_buffer {
+        *err += &format!(
+            "grepr: {}: larger than --max-buffer, searching line by line\n",
+            display_name(filename)
+        );
+        return Ok(Err(Box::new(io::Cursor::new(buffer).chain(file))));
+    }
+    Ok(Ok(String::from_utf8_lossy(&buffer).into_owned()))
+}
+
+/// The name to print for an input, with stdin labeled as GNU grep does
+fn display_name(filename: &str) -> &str {
+    match filename {
+        "-" => "(standard input)",
+        _ => filename,
+    }
+}
+
+/// Like GNU grep, treat input with a NUL byte or invalid UTF-8 near the
+/// start as binary. A character cut off at the end of the buffer is fine.
+fn is_binary(file: &mut impl BufRead) -> bool {
+    file.fill_buf().is_ok_and(|buf| {
+        buf.contains(&0) || str::from_utf8(buf).is_err_and(|e| e.error_len().is_some())
+    })
+}
 
-    Ok(())
+/// Append a line to `line` like `read_line`, but replace any bytes that
+/// are not UTF-8 instead of failing, so binary input can still be searched
+fn read_line_lossy<T: BufRead>(file: &mut T, line: &mut String) -> io::Result<usize> {
+    let mut buf = vec![];
+    let bytes = file.read_until(b'\n', &mut buf)?;
+    line.push_str(&String::from_utf8_lossy(&buf));
+    Ok(bytes)
 }
 
 fn open(filename: &str) -> MyResult<Box<dyn BufRead>> {
@@ -123,28 +815,144 @@ fn open(filename: &str) -> MyResult<Box<dyn BufRead>> {
     }
 }
 
+/// Find the matching lines, each with the byte offset where it starts
 fn find_lines<T: BufRead>(
     mut file: T,
     pattern: &Regex,
     invert_match: bool,
-) -> MyResult<Vec<String>> {
+) -> MyResult<Vec<(usize, String)>> {
     let mut line = String::new();
     let mut matches = vec![];
+    let mut offset = 0;
 
     loop {
-        let bytes = file.read_line(&mut line)?;
+        let bytes = read_line_lossy(&mut file, &mut line)?;
         if bytes == 0 {
             break;
         }
         if pattern.is_match(&line) ^ invert_match {
-            matches.push(mem::take(&mut line));
+            matches.push((offset, mem::take(&mut line)));
+        }
+        offset += bytes;
+        line.clear();
+    }
+    Ok(matches)
+}
+
+/// Count every match on every line, so a line matching twice counts twice
+fn count_matches<T: BufRead>(mut file: T, pattern: &Regex) -> MyResult<usize> {
+    let mut line = String::new();
+    let mut count = 0;
+
+    loop {
+        let bytes = read_line_lossy(&mut file, &mut line)?;
+        if bytes == 0 {
+            break;
+        }
+        count += pattern.find_iter(&line).count();
+        line.clear();
+    }
+    Ok(count)
+}
+
+/// Find the matches in a whole text, which may span lines, giving each
+/// line they touch once, with the byte offset where it starts
+fn find_multiline(text: &str, pattern: &Regex) -> Vec<(usize, String)> {
+    let mut spans: Vec<(usize, usize)> = vec![];
+    for found in pattern.find_iter(text) {
+        let start = text[..found.start()].rfind('\n').map_or(0, |i| i + 1);
+        let end = if found.end() > found.start() && text[..found.end()].ends_with('\n') {
+            found.end()
+        } else {
+            text[found.end()..]
+                .find('\n')
+                .map_or(text.len(), |i| found.end() + i + 1)
+        };
+        match spans.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => spans.push((start, end)),
+        }
+    }
+    spans
+        .into_iter()
+        .filter(|(start, end)| start < end)
+        .flat_map(|(start, end)| {
+            text[start..end]
+                .split_inclusive('\n')
+                .scan(start, |offset, line| {
+                    let found = (*offset, line.to_string());
+                    *offset += line.len();
+                    Some(found)
+                })
+        })
+        .collect()
+}
+
+/// Like [`find_lines`], but also keeps up to `before`/`after` non-matching
+/// lines around each match. Each entry holds the 0-based line index,
+/// whether the line matched, and the line itself.
+fn find_context<T: BufRead>(
+    mut file: T,
+    pattern: &Regex,
+    invert_match: bool,
+    before: usize,
+    after: usize,
+) -> MyResult<Vec<(usize, bool, String)>> {
+    let mut line = String::new();
+    let mut lines = vec![];
+    let mut leading = VecDeque::with_capacity(before);
+    let mut trailing = 0;
+
+    for index in 0.. {
+        let bytes = read_line_lossy(&mut file, &mut line)?;
+        if bytes == 0 {
+            break;
+        }
+        let text = mem::take(&mut line);
+        if pattern.is_match(&text) ^ invert_match {
+            lines.extend(leading.drain(..).map(|(i, l)| (i, false, l)));
+            lines.push((index, true, text));
+            trailing = after;
+        } else if trailing > 0 {
+            lines.push((index, false, text));
+            trailing -= 1;
+        } else if before > 0 {
+            if leading.len() == before {
+                leading.pop_front();
+            }
+            leading.push_back((index, text));
+        }
+    }
+    Ok(lines)
+}
+
+/// Find every match in the file, yielding its 1-based line number and
+/// 1-based byte column along with the line without its ending. A line
+/// with several matches appears once per match.
+fn find_columns<T: BufRead>(mut file: T, pattern: &Regex) -> MyResult<Vec<(usize, usize, String)>> {
+    let mut line = String::new();
+    let mut matches = vec![];
+
+    for line_num in 1.. {
+        let bytes = read_line_lossy(&mut file, &mut line)?;
+        if bytes == 0 {
+            break;
+        }
+        let text = line.trim_end_matches(&['\r', '\n'][..]);
+        for found in pattern.find_iter(text) {
+            matches.push((line_num, found.start() + 1, text.to_string()));
         }
         line.clear();
     }
     Ok(matches)
 }
 
-fn find_files(paths: &[String], recursive: bool) -> Vec<MyResult<String>> {
+fn find_files(
+    paths: &[String],
+    recursive: bool,
+    sort: Option<SortBy>,
+    reverse: bool,
+) -> Vec<MyResult<String>> {
     let mut results = vec![];
 
     for path in paths {
@@ -154,11 +962,28 @@ fn find_files(paths: &[String], recursive: bool) -> Vec<MyResult<String>> {
                 Ok(metadata) => {
                     if metadata.is_dir() {
                         if recursive {
-                            for entry in WalkDir::new(path)
+                            let walker = WalkDir::new(path);
+                            let walker = if sort == Some(SortBy::Path) {
+                                walker.sort_by_file_name()
+                            } else {
+                                walker
+                            };
+                            let mut entries: Vec<_> = walker
                                 .into_iter()
                                 .flatten()
                                 .filter(|e| e.file_type().is_file())
-                            {
+                                .collect();
+                            // Modification times order the whole tree, not
+                            // just the entries of each directory
+                            if sort == Some(SortBy::Modified) {
+                                entries.sort_by_cached_key(|e| {
+                                    e.metadata().ok().and_then(|m| m.modified().ok())
+                                });
+                            }
+                            if reverse {
+                                entries.reverse();
+                            }
+                            for entry in entries {
                                 results.push(Ok(entry.path().display().to_string()));
                             }
                         } else {
@@ -178,7 +1003,10 @@ fn find_files(paths: &[String], recursive: bool) -> Vec<MyResult<String>> {
 
 #[cfg(test)]
 mod tests {
-    use super::{find_files, find_lines};
+    use super::{
+        build_pattern, count_matches, find_columns, find_context, find_files, find_lines,
+        find_multiline, parse_size, SortBy, WordBoundary,
+    };
     use rand::{distributions::Alphanumeric, Rng};
     use regex::{Regex, RegexBuilder};
     use std::io::Cursor;
@@ -186,19 +1014,19 @@ mod tests {
     #[test]
     fn test_find_files() {
         // Verify that the function finds a file known to exist
-        let files = find_files(&["./tests/inputs/fox.txt".to_string()], false);
+        let files = find_files(&["./tests/inputs/fox.txt".to_string()], false, None, false);
         assert_eq!(files.len(), 1);
         assert_eq!(files[0].as_ref().unwrap(), "./tests/inputs/fox.txt");
 
         // The function should reject a directory without the recursive option
-        let files = find_files(&["./tests/inputs".to_string()], false);
+        let files = find_files(&["./tests/inputs".to_string()], false, None, false);
         assert_eq!(files.len(), 1);
         if let Err(e) = &files[0] {
             assert_eq!(e.to_string(), "./tests/inputs is a directory");
         }
 
         // Verify the function recurses to find four files in the directory
-        let res = find_files(&["./tests/inputs".to_string()], true);
+        let res = find_files(&["./tests/inputs".to_string()], true, None, false);
         let mut files: Vec<String> = res
             .iter()
             .map(|r| r.as_ref().unwrap().replace("\\", "/"))
@@ -215,6 +1043,18 @@ mod tests {
             ]
         );
 
+        // Sorting yields the same files without the caller sorting them
+        let sorted: Vec<String> = find_files(
+            &["./tests/inputs".to_string()],
+            true,
+            Some(SortBy::Path),
+            false,
+        )
+        .iter()
+        .map(|r| r.as_ref().unwrap().replace("\\", "/"))
+        .collect();
+        assert_eq!(sorted, files);
+
         // Generate a random string to represent a nonexistent file
         let bad: String = rand::thread_rng()
             .sample_iter(&Alphanumeric)
@@ -223,7 +1063,7 @@ mod tests {
             .collect();
 
         // Verify that the function returns the bad file as an error
-        let files = find_files(&[bad], false);
+        let files = find_files(&[bad], false, None, false);
         assert_eq!(files.len(), 1);
         assert!(files[0].is_err());
     }
@@ -258,5 +1098,166 @@ mod tests {
         let matches = find_lines(Cursor::new(&text), &re2, true);
         assert!(matches.is_ok());
         assert_eq!(matches.unwrap().len(), 1);
+
+        // Offsets count bytes, including the \r of a CRLF ending
+        let matches = find_lines(Cursor::new(&text), &re2, false).unwrap();
+        let offsets: Vec<_> = matches.iter().map(|(offset, _)| *offset).collect();
+        assert_eq!(offsets, [0, 13]);
+        let text = "été\nor\n";
+        let matches = find_lines(Cursor::new(text), &re1, false).unwrap();
+        assert_eq!(matches, [(6, "or\n".to_string())]);
+    }
+
+    #[test]
+    fn test_count_matches() {
+        let text = b"the cat and the hat\nno match\nThe end\n";
+
+        let re = Regex::new("the").unwrap();
+        let count = count_matches(Cursor::new(&text), &re);
+        assert_eq!(count.unwrap(), 2);
+
+        let re = RegexBuilder::new("the")
+            .case_insensitive(true)
+            .build()
+            .unwrap();
+        let count = count_matches(Cursor::new(&text), &re);
+        assert_eq!(count.unwrap(), 3);
+
+        let re = Regex::new("dog").unwrap();
+        let count = count_matches(Cursor::new(&text), &re);
+        assert_eq!(count.unwrap(), 0);
+    }
+
+    #[test]
+    fn test_find_context() {
+        let text = b"one\ntwo\nthree\nfour\nfive\nsix\nseven\n";
+        let re = Regex::new("three|seven").unwrap();
+
+        // One line of context on either side of each match
+        let lines = find_context(Cursor::new(&text), &re, false, 1, 1).unwrap();
+        let indexes: Vec<_> = lines.iter().map(|(i, m, _)| (*i, *m)).collect();
+        assert_eq!(
+            indexes,
+            [(1, false), (2, true), (3, false), (5, false), (6, true)]
+        );
+
+        // No context keeps only the matching lines
+        let lines = find_context(Cursor::new(&text), &re, false, 0, 0).unwrap();
+        assert_eq!(lines.len(), 2);
+
+        // Overlapping context is not repeated
+        let lines = find_context(Cursor::new(&text), &re, false, 4, 4).unwrap();
+        assert_eq!(lines.len(), 7);
+    }
+
+    #[test]
+    fn test_find_columns() {
+        let text = b"the cat\r\nno match\nthe other one\n";
+        let re = Regex::new("the").unwrap();
+        let matches = find_columns(Cursor::new(&text), &re).unwrap();
+        let spans: Vec<_> = matches.iter().map(|(l, c, _)| (*l, *c)).collect();
+        assert_eq!(spans, [(1, 1), (3, 1), (3, 6)]);
+        assert_eq!(matches[0].2, "the cat");
+    }
+
+    #[test]
+    fn test_build_pattern() {
+        let patterns = vec!["fox".to_string(), "^The".to_string()];
+        let re = build_pattern(&patterns, false, None, false).unwrap();
+        assert!(re.is_match("brown fox"));
+        assert!(re.is_match("The end"));
+        assert!(!re.is_match("At the end"));
+
+        let re = build_pattern(&patterns, true, None, false).unwrap();
+        assert!(re.is_match("the end"));
+
+        let res = build_pattern(&["ok".to_string(), "*foo".to_string()], false, None, false);
+        assert_eq!(res.unwrap_err().to_string(), "Invalid pattern \"*foo\"");
+
+        assert!(build_pattern(&[], false, None, false).is_err());
+    }
+
+    #[test]
+    fn test_build_pattern_words() {
+        let patterns = vec!["caf".to_string(), "東京".to_string()];
+        let re = build_pattern(&patterns, false, Some(WordBoundary::Unicode), false).unwrap();
+        assert!(re.is_match("a caf here"));
+        // Accented and CJK letters are word characters
+        assert!(!re.is_match("café"));
+        assert!(!re.is_match("東京都"));
+        assert!(re.is_match("in 東京 now"));
+
+        // Only ASCII counts as a word, so "é" and "都" end one
+        let re = build_pattern(&patterns, false, Some(WordBoundary::Ascii), false).unwrap();
+        assert!(re.is_match("café"));
+        assert!(re.is_match("a caf here"));
+
+        let re = build_pattern(
+            &["fox".to_string()],
+            true,
+            Some(WordBoundary::Unicode),
+            false,
+        )
+        .unwrap();
+        assert!(re.is_match("The FOX."));
+        assert!(!re.is_match("foxes"));
+    }
+
+    #[test]
+    fn test_find_multiline() {
+        let lines = |found: &[(usize, &str)]| -> Vec<(usize, String)> {
+            found.iter().map(|&(i, l)| (i, l.to_string())).collect()
+        };
+        let text = "one\ntwo\nthree\nfour\nfive";
+        let re = Regex::new(r"two\nth").unwrap();
+        assert_eq!(
+            find_multiline(text, &re),
+            lines(&[(4, "two\n"), (8, "three\n")])
+        );
+
+        // Without dotall, . stops at the end of a line
+        let re = Regex::new("o.t").unwrap();
+        assert!(find_multiline(text, &re).is_empty());
+        let re = RegexBuilder::new("o.t")
+            .dot_matches_new_line(true)
+            .build()
+            .unwrap();
+        assert_eq!(
+            find_multiline(text, &re),
+            lines(&[(4, "two\n"), (8, "three\n")])
+        );
+
+        // Each line is given once, however many matches touch it
+        let re = Regex::new("o|f").unwrap();
+        assert_eq!(
+            find_multiline(text, &re),
+            lines(&[(0, "one\n"), (4, "two\n"), (14, "four\n"), (19, "five")])
+        );
+        let re = Regex::new("e\n").unwrap();
+        assert_eq!(
+            find_multiline(text, &re),
+            lines(&[(0, "one\n"), (8, "three\n")])
+        );
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("0").unwrap(), 0);
+        assert_eq!(parse_size("500").unwrap(), 500);
+        assert_eq!(parse_size("500b").unwrap(), 500);
+        assert_eq!(parse_size("1k").unwrap(), 1024);
+        assert_eq!(parse_size("1K").unwrap(), 1024);
+        assert_eq!(parse_size("2M").unwrap(), 2 * 1024 * 1024);
+        assert_eq!(parse_size("1G").unwrap(), 1024 * 1024 * 1024);
+        assert!(parse_size("").is_err());
+        assert!(parse_size("K").is_err());
+        assert!(parse_size("1Q").is_err());
+        assert!(parse_size("+1K").is_err());
+        assert!(parse_size("1.5K").is_err());
+
+        // Sizes that do not fit in a u64 are errors rather than wrapping
+        assert_eq!(parse_size("16777215G").unwrap(), 16777215 << 30);
+        assert!(parse_size("17179869184G").is_err());
+        assert!(parse_size("18446744073709551616").is_err());
     }
 }
@@ -12,6 +12,7 @@ const EMPTY: &str = "tests/inputs/empty.txt";
 const FOX: &str = "tests/inputs/fox.txt";
 const NOBODY: &str = "tests/inputs/nobody.txt";
 const INPUTS_DIR: &str = "tests/inputs";
+const LATIN1: &str = "tests/binary/latin1.txt";
 
 // --------------------------------------------------
 fn gen_bad_file() -> String {
@@ -42,7 +43,7 @@ fn dies_no_args() -> TestResult {
 #[test]
 fn dies_bad_pattern() -> TestResult {
     Command::cargo_bin(PRG)?
-        .args(&["*foo", FOX])
+        .args(["*foo", FOX])
         .assert()
         .failure()
         .stderr(predicate::str::contains("Invalid pattern \"*foo\""));
@@ -55,7 +56,7 @@ fn warns_bad_file() -> TestResult {
     let bad = gen_bad_file();
     let expected = format!("{}: .* [(]os error 2[)]", bad);
     Command::cargo_bin(PRG)?
-        .args(&["foo", &bad])
+        .args(["foo", &bad])
         .assert()
         .stderr(predicate::str::is_match(expected)?);
     Ok(())
@@ -64,15 +65,13 @@ fn warns_bad_file() -> TestResult {
 // --------------------------------------------------
 fn run(args: &[&str], expected_file: &str) -> TestResult {
     let windows_file = format!("{}.windows", expected_file);
-    let expected_file = if os_type().unwrap() == "Windows"
-        && Path::new(&windows_file).is_file()
-    {
+    let expected_file = if os_type().unwrap() == "Windows" && Path::new(&windows_file).is_file() {
         &windows_file
     } else {
         expected_file
     };
 
-    let expected = fs::read_to_string(&expected_file)?;
+    let expected = fs::read_to_string(expected_file)?;
 
     Command::cargo_bin(PRG)?
         .args(args)
@@ -195,6 +194,66 @@ fn insensitive_count() -> TestResult {
     )
 }
 
+// --------------------------------------------------
+#[test]
+fn count_matches() -> TestResult {
+    // -c counts the one line, --count-matches each of its matches
+    Command::cargo_bin(PRG)?
+        .args(["-c", "ab"])
+        .write_stdin("ab ab ab\nnothing\n")
+        .assert()
+        .success()
+        .stdout("1\n");
+    Command::cargo_bin(PRG)?
+        .args(["--count-matches", "ab"])
+        .write_stdin("ab ab ab\nnothing\n")
+        .assert()
+        .success()
+        .stdout("3\n");
+    Command::cargo_bin(PRG)?
+        .args(["--count-matches", "-i", "ab"])
+        .write_stdin("ab AB Ab\nnothing\n")
+        .assert()
+        .success()
+        .stdout("3\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_count_matches_invert() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--count-matches", "-v", "ab", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn byte_offset() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["-b", "morning|solemnest", BUSTLE])
+        .assert()
+        .success()
+        .stdout("22:The morning after death\n46:Is solemnest of industries\n");
+    Command::cargo_bin(PRG)?
+        .args(["-H", "--byte-offset", "morning", BUSTLE])
+        .assert()
+        .success()
+        .stdout(format!("{}:22:The morning after death\n", BUSTLE));
+
+    // Offsets are in bytes, counting each \r\n as two
+    Command::cargo_bin(PRG)?
+        .args(["-b", "c"])
+        .write_stdin("a\r\né\r\nc\r\n")
+        .assert()
+        .success()
+        .stdout("7:c\r\n");
+    Ok(())
+}
+
 // --------------------------------------------------
 #[test]
 fn nobody_count() -> TestResult {
@@ -228,13 +287,131 @@ fn insensitive_count_multiple() -> TestResult {
     )
 }
 
+// --------------------------------------------------
+#[test]
+fn context() -> TestResult {
+    run(
+        &["-C", "1", "bustle|eternity", BUSTLE],
+        "tests/expected/bustle.txt.bustle.eternity.context",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn context_count() -> TestResult {
+    // Context only affects display, so the count is the same as without it
+    run(
+        &["-C", "2", "-c", "The", BUSTLE],
+        "tests/expected/bustle.txt.the.capitalized.count",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_context() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["-A", "x", "foo", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("\"x\" not a valid context length"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn patterns_from_stdin() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["-f", "-", BUSTLE])
+        .write_stdin("morning\neternity\n")
+        .assert()
+        .success()
+        .stdout("The morning after death\nUntil eternity.\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_patterns_and_input_from_stdin() -> TestResult {
+    for args in [&["-f", "-"][..], &["-f", "-", "-"], &["-f", "-", FOX, "-"]] {
+        Command::cargo_bin(PRG)?
+            .args(args)
+            .write_stdin("fox\n")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(
+                "Cannot read both patterns and input from stdin",
+            ));
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn vimgrep() -> TestResult {
+    run(
+        &["--vimgrep", "-i", "the", FOX],
+        "tests/expected/fox.txt.the.i.vimgrep",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn vimgrep_stdin() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--vimgrep", "fox"])
+        .write_stdin("The quick brown fox\n")
+        .assert()
+        .success()
+        .stdout("(standard input):1:17:The quick brown fox\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+fn run_sorted(args: &[&str], expected: &[&str]) -> TestResult {
+    let cmd = Command::cargo_bin(PRG)?.args(args).assert().success();
+    let stdout = String::from_utf8(cmd.get_output().stdout.clone())?;
+    let mut lines: Vec<_> = stdout.lines().map(|l| l.replace('\\', "/")).collect();
+    lines.sort();
+    assert_eq!(lines, expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn recursive_count_skips_zero() -> TestResult {
+    run_sorted(&["-rc", "fox", INPUTS_DIR], &["tests/inputs/fox.txt:1"])
+}
+
+// --------------------------------------------------
+#[test]
+fn recursive_count_include_zero() -> TestResult {
+    run_sorted(
+        &["-rc", "--include-zero", "fox", INPUTS_DIR],
+        &[
+            "tests/inputs/bustle.txt:0",
+            "tests/inputs/empty.txt:0",
+            "tests/inputs/fox.txt:1",
+            "tests/inputs/nobody.txt:0",
+        ],
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn count_only_nonzero() -> TestResult {
+    run_sorted(
+        &["-c", "--count-only-nonzero", "The", BUSTLE, EMPTY, FOX],
+        &["tests/inputs/bustle.txt:3", "tests/inputs/fox.txt:1"],
+    )
+}
+
 // --------------------------------------------------
 #[test]
 fn warns_dir_not_recursive() -> TestResult {
     let stdout = "tests/inputs/fox.txt:\
         The quick brown fox jumps over the lazy dog.";
     Command::cargo_bin(PRG)?
-        .args(&["fox", INPUTS_DIR, FOX])
+        .args(["fox", INPUTS_DIR, FOX])
         .assert()
         .stderr(predicate::str::contains("tests/inputs is a directory"))
         .stdout(predicate::str::contains(stdout));
@@ -245,8 +422,7 @@ fn warns_dir_not_recursive() -> TestResult {
 #[test]
 fn stdin() -> TestResult {
     let input = fs::read_to_string(BUSTLE)?;
-    let expected =
-        fs::read_to_string("tests/expected/bustle.txt.the.capitalized")?;
+    let expected = fs::read_to_string("tests/expected/bustle.txt.the.capitalized")?;
 
     Command::cargo_bin(PRG)?
         .arg("The")
@@ -266,14 +442,442 @@ fn stdin_insensitive_count() -> TestResult {
         input += &fs::read_to_string(file)?;
     }
 
-    let expected_file =
-        "tests/expected/the.recursive.insensitive.count.stdin";
+    let expected_file = "tests/expected/the.recursive.insensitive.count.stdin";
     let expected = fs::read_to_string(expected_file)?;
 
     Command::cargo_bin(PRG)?
-        .args(&["-ci", "the", "-"])
+        .args(["-ci", "the", "-"])
         .write_stdin(input)
         .assert()
         .stdout(expected);
     Ok(())
 }
+
+// --------------------------------------------------
+#[test]
+fn stdin_with_filename() -> TestResult {
+    let input = fs::read_to_string(BUSTLE)?;
+    let expected = fs::read_to_string("tests/expected/bustle.txt.the.capitalized")?;
+
+    // Stdin alone gets no prefix, even with -H
+    Command::cargo_bin(PRG)?
+        .args(["-H", "The"])
+        .write_stdin(input.clone())
+        .assert()
+        .stdout(expected);
+
+    // Alongside other files it is labeled "(standard input)"
+    Command::cargo_bin(PRG)?
+        .args(["-c", "The", "-", FOX])
+        .write_stdin(input)
+        .assert()
+        .stdout("(standard input):3\ntests/inputs/fox.txt:1\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn with_and_no_filename() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["-H", "fox", FOX])
+        .assert()
+        .stdout("tests/inputs/fox.txt:The quick brown fox jumps over the lazy dog.\n");
+
+    Command::cargo_bin(PRG)?
+        .args(["--no-filename", "-c", "The", BUSTLE, FOX])
+        .assert()
+        .stdout("3\n1\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn regexp_without_positional() -> TestResult {
+    run(
+        &["-e", "The", BUSTLE],
+        "tests/expected/bustle.txt.the.capitalized",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn regexps_combined() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["-c", "-e", "fox", "-e", "Nobody", FOX, NOBODY])
+        .assert()
+        .success()
+        .stdout("tests/inputs/fox.txt:1\ntests/inputs/nobody.txt:2\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn binary_file() -> TestResult {
+    let file = std::env::temp_dir().join(format!("grepr-binary-{}", std::process::id()));
+    fs::write(&file, b"header\0\xff\xfe\nfox in the data\n")?;
+    let name = file.display().to_string();
+
+    Command::cargo_bin(PRG)?
+        .args(["fox", &name])
+        .assert()
+        .success()
+        .stdout(format!("Binary file {} matches\n", name));
+
+    for flag in ["-a", "--text", "--include-binary"] {
+        Command::cargo_bin(PRG)?
+            .args([flag, "fox", &name])
+            .assert()
+            .success()
+            .stdout("fox in the data\n");
+    }
+
+    // Every way of printing lines only says that the file matches
+    for args in [&["--vimgrep"][..], &["-C", "1"], &["-U"], &["-U", "-b"]] {
+        Command::cargo_bin(PRG)?
+            .args(args)
+            .args(["fox", &name])
+            .assert()
+            .success()
+            .stdout(format!("Binary file {} matches\n", name));
+    }
+
+    // Counts are the same as for text
+    Command::cargo_bin(PRG)?
+        .args(["-c", "fox", &name])
+        .assert()
+        .success()
+        .stdout("1\n");
+    Command::cargo_bin(PRG)?
+        .args(["--count-matches", "[ad]", &name])
+        .assert()
+        .success()
+        .stdout("5\n");
+
+    fs::remove_file(&file)?;
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn invalid_utf8() -> TestResult {
+    // Bytes that are not UTF-8 make a file binary even without a NUL
+    Command::cargo_bin(PRG)?
+        .args(["fox", LATIN1])
+        .assert()
+        .success()
+        .stdout(format!("Binary file {} matches\n", LATIN1));
+
+    // They are still searched, and printed as replacement characters
+    Command::cargo_bin(PRG)?
+        .args(["-a", "fox", LATIN1])
+        .assert()
+        .success()
+        .stdout("caf\u{FFFD} fox\nfox\n");
+    for args in [&["-c"][..], &["-ac"]] {
+        Command::cargo_bin(PRG)?
+            .args(args)
+            .args(["fox", LATIN1])
+            .assert()
+            .success()
+            .stdout("2\n");
+    }
+    Command::cargo_bin(PRG)?
+        .args(["-a", "--vimgrep", "data", LATIN1])
+        .assert()
+        .success()
+        .stdout(format!("{}:2:8:\u{FFFD}\u{FFFD} data\n", LATIN1));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn sorted_files_with_threads() -> TestResult {
+    let dir = std::env::temp_dir().join(format!("grepr-sorted-{}", std::process::id()));
+    for sub in ["b/d", "a", "c"] {
+        fs::create_dir_all(dir.join(sub))?;
+    }
+    let files = [
+        "a/1.txt",
+        "a/2.txt",
+        "b/3.txt",
+        "b/d/4.txt",
+        "c/5.txt",
+        "z.txt",
+    ];
+    for (i, name) in files.iter().enumerate() {
+        fs::write(dir.join(name), format!("match {}\nskip\nmatch again\n", i))?;
+    }
+
+    let root = dir.display().to_string();
+    let expected: String = files
+        .iter()
+        .enumerate()
+        .map(|(i, name)| {
+            let path = Path::new(&root).join(name).display().to_string();
+            format!("{0}:match {1}\n{0}:match again\n", path, i)
+        })
+        .collect();
+
+    for threads in ["1", "4"] {
+        Command::cargo_bin(PRG)?
+            .args(["-r", "--sort-files", "--threads", threads, "match", &root])
+            .assert()
+            .success()
+            .stdout(expected.clone());
+    }
+
+    fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_threads() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["-j", "0", "fox", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "\"0\" not a valid number of threads",
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn sort_modified() -> TestResult {
+    use std::time::{Duration, SystemTime};
+
+    let dir = std::env::temp_dir().join(format!("grepr-modified-{}", std::process::id()));
+    fs::create_dir_all(dir.join("sub"))?;
+    // Listed oldest first, which is not the path order
+    let files = ["sub/b.log", "c.log", "a.log"];
+    let start = SystemTime::now() - Duration::from_secs(3600);
+    for (i, name) in files.iter().enumerate() {
+        let path = dir.join(name);
+        fs::write(&path, format!("entry {}\n", i))?;
+        fs::File::options()
+            .write(true)
+            .open(&path)?
+            .set_modified(start + Duration::from_secs(60 * i as u64))?;
+    }
+
+    let root = dir.display().to_string();
+    let line = |i: usize| {
+        let path = Path::new(&root).join(files[i]).display().to_string();
+        format!("{}:entry {}\n", path, i)
+    };
+
+    Command::cargo_bin(PRG)?
+        .args(["-r", "--sort=modified", "entry", &root])
+        .assert()
+        .success()
+        .stdout(format!("{}{}{}", line(0), line(1), line(2)));
+
+    Command::cargo_bin(PRG)?
+        .args(["-r", "--sort", "modified", "--sort-reverse", "entry", &root])
+        .assert()
+        .success()
+        .stdout(format!("{}{}{}", line(2), line(1), line(0)));
+
+    Command::cargo_bin(PRG)?
+        .args(["-r", "--sort=path", "entry", &root])
+        .assert()
+        .success()
+        .stdout(format!("{}{}{}", line(2), line(1), line(0)));
+
+    fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn debug_stats() -> TestResult {
+    let cmd = Command::cargo_bin(PRG)?
+        .args(["--debug", "fox", FOX, NOBODY])
+        .assert()
+        .success()
+        .stdout(format!(
+            "{}:The quick brown fox jumps over the lazy dog.\n",
+            FOX
+        ));
+    let stderr = String::from_utf8(cmd.get_output().stderr.clone())?;
+    let lines: Vec<_> = stderr.lines().collect();
+    assert_eq!(lines.len(), 2);
+    for (line, (name, size)) in lines.iter().zip([(FOX, 45), (NOBODY, 248)]) {
+        let prefix = format!("grepr: debug: {}: {} bytes in ", name, size);
+        assert!(line.starts_with(&prefix), "{}", line);
+        assert!(line.ends_with(" bytes/sec)"), "{}", line);
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn word_regexp() -> TestResult {
+    let input = "naïve café\ncaf au lait\n東京都\n東京 tower\n";
+    Command::cargo_bin(PRG)?
+        .args(["-w", "-e", "caf", "-e", "東京"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout("caf au lait\n東京 tower\n");
+
+    Command::cargo_bin(PRG)?
+        .args(["-w", "--ascii-boundaries", "caf"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout("naïve café\ncaf au lait\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn tar_members() -> TestResult {
+    let dir = std::env::temp_dir().join(format!("grepr-tar-{}", std::process::id()));
+    fs::create_dir_all(&dir)?;
+    let archive = dir.join("logs.tar");
+    let mut builder = tar::Builder::new(fs::File::create(&archive)?);
+    builder.append_path_with_name(FOX, "fox.txt")?;
+    builder.append_path_with_name(BUSTLE, "poems/bustle.txt")?;
+    builder.finish()?;
+    drop(builder);
+    let archive = archive.display().to_string();
+
+    Command::cargo_bin(PRG)?
+        .args(["-i", "the", &archive])
+        .assert()
+        .success()
+        .stdout(format!(
+            "{0}:fox.txt:The quick brown fox jumps over the lazy dog.\n\
+             {0}:poems/bustle.txt:The bustle in a house\n\
+             {0}:poems/bustle.txt:The morning after death\n\
+             {0}:poems/bustle.txt:The sweeping up the heart,\n",
+            archive
+        ));
+
+    Command::cargo_bin(PRG)?
+        .args(["-c", "fox", &archive, FOX])
+        .assert()
+        .success()
+        .stdout(format!(
+            "{0}:fox.txt:1\n{0}:poems/bustle.txt:0\n{1}:1\n",
+            archive, FOX
+        ));
+
+    fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn multiline_dotall() -> TestResult {
+    let input = "The quick brown fox\njumps over\nthe lazy dog.\n";
+    Command::cargo_bin(PRG)?
+        .args(["-U", r"fox\njumps"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout("The quick brown fox\njumps over\n");
+
+    Command::cargo_bin(PRG)?
+        .args(["-U", "fox.jumps"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout("");
+
+    Command::cargo_bin(PRG)?
+        .args(["-U", "--dotall", "fox.jumps.*lazy"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(input);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn multiline_prefix_each_line() -> TestResult {
+    let file = std::env::temp_dir().join(format!("grepr-span-{}", std::process::id()));
+    fs::write(&file, "foo\nbar\nbaz\n")?;
+    let name = file.display().to_string();
+
+    // A match over two lines names the file on both
+    Command::cargo_bin(PRG)?
+        .args(["-U", r"o\nb", &name, &name])
+        .assert()
+        .success()
+        .stdout(format!(
+            "{name}:foo\n{name}:bar\n{name}:foo\n{name}:bar\n",
+            name = name
+        ));
+
+    fs::remove_file(&file)?;
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn multiline_count() -> TestResult {
+    // -c counts lines, even when adjacent matches join up
+    Command::cargo_bin(PRG)?
+        .args(["-U", "-c", "o"])
+        .write_stdin("o\no\n")
+        .assert()
+        .success()
+        .stdout("2\n");
+
+    // --count-matches counts matches that span lines
+    Command::cargo_bin(PRG)?
+        .args(["-U", "--count-matches", r"o\nt"])
+        .write_stdin("o\nt\no\nt\n")
+        .assert()
+        .success()
+        .stdout("2\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn multiline_max_buffer() -> TestResult {
+    let file = std::env::temp_dir().join(format!("grepr-large-{}", std::process::id()));
+    let mut contents: String = (0..10_000).map(|i| format!("line {}\n", i)).collect();
+    contents += "needle\nin a haystack\n";
+    fs::write(&file, &contents)?;
+    let name = file.display().to_string();
+
+    Command::cargo_bin(PRG)?
+        .args(["-U", "--max-buffer", "1M", r"needle\nin", &name])
+        .assert()
+        .success()
+        .stdout("needle\nin a haystack\n")
+        .stderr("");
+
+    // Too large to buffer, the search goes line by line where no match
+    // can span two lines
+    Command::cargo_bin(PRG)?
+        .args(["-U", "--max-buffer", "4K", r"needle\nin", &name])
+        .assert()
+        .success()
+        .stdout("")
+        .stderr(format!(
+            "grepr: {}: larger than --max-buffer, searching line by line\n",
+            name
+        ));
+    Command::cargo_bin(PRG)?
+        .args(["-U", "--max-buffer", "4K", "needle", &name])
+        .assert()
+        .success()
+        .stdout("needle\n");
+
+    Command::cargo_bin(PRG)?
+        .args(["-U", "--max-buffer", "4X", "needle", &name])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("\"4X\" not a valid size"));
+
+    fs::remove_file(&file)?;
+    Ok(())
+}
@@ -1,12 +1,23 @@
 mod owner;
 
-use chrono::{DateTime, Local};
+use ansi_term::Colour;
+use chrono::{DateTime, Local, TimeZone};
 use clap::{App, Arg};
 use owner::Owner;
+use std::cmp::Reverse;
 use std::fs;
-use std::os::unix::fs::MetadataExt;
-use std::{error::Error, path::PathBuf};
+use std::io::{self, IsTerminal};
+#[cfg(unix)]
+use std::os::unix::fs::{FileTypeExt, MetadataExt};
+use std::{
+    error::Error,
+    fmt,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
 use tabular::{Row, Table};
+use terminal_size::{terminal_size, Width};
+#[cfg(unix)]
 use users::{get_group_by_gid, get_user_by_uid};
 
 type MyResult<T> = Result<T, Box<dyn Error>>;
@@ -16,6 +27,45 @@ pub struct Config {
     paths: Vec<String>,
     long: bool,
     show_hidden: bool,
+    quoting_style: QuotingStyle,
+    indicator_style: IndicatorStyle,
+    human_readable: bool,
+    recursive: bool,
+    sort_by: SortBy,
+    reverse: bool,
+    one_per_line: bool,
+    color: ColorMode,
+}
+
+/// The order of listed entries; times and sizes put the newest and
+/// largest first, with ties broken by name
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortBy {
+    Name,
+    Time,
+    Size,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuotingStyle {
+    Literal,
+    Shell,
+    C,
+}
+
+/// When to color names by file type; auto only colors for a terminal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorMode {
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndicatorStyle {
+    None,
+    Slash,
+    Classify,
 }
 
 pub fn get_args() -> MyResult<Config> {
@@ -42,40 +92,310 @@ pub fn get_args() -> MyResult<Config> {
                 .long("all")
                 .help("Show all files"),
         )
+        .arg(
+            Arg::with_name("human_readable")
+                .short("h")
+                .long("human-readable")
+                .help("Show sizes like 1.5K and 23M in the long listing"),
+        )
+        .arg(
+            Arg::with_name("recursive")
+                .short("R")
+                .long("recursive")
+                .help("List subdirectories recursively"),
+        )
+        .arg(
+            Arg::with_name("time")
+                .short("t")
+                .help("Sort by modification time, newest first"),
+        )
+        .arg(
+            Arg::with_name("size")
+                .short("S")
+                .conflicts_with("time")
+                .help("Sort by size, largest first"),
+        )
+        .arg(
+            Arg::with_name("reverse")
+                .short("r")
+                .long("reverse")
+                .help("Reverse the sort order"),
+        )
+        .arg(
+            Arg::with_name("one_per_line")
+                .short("1")
+                .help("List one entry per line"),
+        )
+        .arg(
+            Arg::with_name("quoting_style")
+                .value_name("WORD")
+                .long("quoting-style")
+                .possible_values(&["literal", "shell", "c"])
+                .default_value("literal")
+                .help("Use quoting style WORD for entry names"),
+        )
+        .arg(
+            Arg::with_name("indicator_style")
+                .value_name("WORD")
+                .long("indicator-style")
+                .possible_values(&["none", "slash", "classify"])
+                .default_value("none")
+                .help("Append indicator with style WORD to entry names"),
+        )
+        .arg(
+            Arg::with_name("classify")
+                .short("F")
+                .long("classify")
+                .help("Append indicator (one of */=@|) to entry names"),
+        )
+        .arg(
+            Arg::with_name("color")
+                .value_name("WHEN")
+                .long("color")
+                .possible_values(&["auto", "always", "never"])
+                .min_values(0)
+                .require_equals(true)
+                .help("Color names by file type WHEN [default: never, or always without WHEN]"),
+        )
         .get_matches();
 
+    let quoting_style = match matches.value_of("quoting_style") {
+        Some("shell") => QuotingStyle::Shell,
+        Some("c") => QuotingStyle::C,
+        _ => QuotingStyle::Literal,
+    };
+
+    let indicator_style = match matches.value_of("indicator_style") {
+        _ if matches.is_present("classify") => IndicatorStyle::Classify,
+        Some("slash") => IndicatorStyle::Slash,
+        Some("classify") => IndicatorStyle::Classify,
+        _ => IndicatorStyle::None,
+    };
+
     Ok(Config {
         paths: matches.values_of_lossy("paths").unwrap(),
         long: matches.is_present("long"),
         show_hidden: matches.is_present("all"),
+        quoting_style,
+        indicator_style,
+        human_readable: matches.is_present("human_readable"),
+        recursive: matches.is_present("recursive"),
+        sort_by: if matches.is_present("time") {
+            SortBy::Time
+        } else if matches.is_present("size") {
+            SortBy::Size
+        } else {
+            SortBy::Name
+        },
+        reverse: matches.is_present("reverse"),
+        one_per_line: matches.is_present("one_per_line"),
+        color: match matches.value_of("color") {
+            _ if !matches.is_present("color") => ColorMode::Never,
+            Some("auto") => ColorMode::Auto,
+            Some("never") => ColorMode::Never,
+            _ => ColorMode::Always,
+        },
     })
 }
 
-pub fn run(config: Config) -> MyResult<()> {
-    let paths = find_files(&config.paths, config.show_hidden)?;
+/// List the paths, giving the number that could not be listed. The
+/// listing of the rest is still shown, but like ls the exit status should
+/// say that not all of them were.
+pub fn run(config: Config) -> MyResult<usize> {
+    if !config.recursive {
+        let (mut paths, num_failed) = find_files(&config.paths, config.show_hidden)?;
+        sort_paths(&mut paths, config.sort_by, config.reverse);
+        // Only the contents of directories get a total, as in ls
+        let dirs_only = config
+            .paths
+            .iter()
+            .all(|path| fs::metadata(path).is_ok_and(|meta| meta.is_dir()));
+        print_paths(&paths, &config, dirs_only)?;
+        return Ok(num_failed);
+    }
+
+    // Like ls -R, files named on the command line come first, then each
+    // directory's contents under a "dir:" header
+    let (dirs, files): (Vec<_>, Vec<_>) = config
+        .paths
+        .iter()
+        .partition(|path| fs::metadata(path).is_ok_and(|meta| meta.is_dir()));
+    let files: Vec<String> = files.into_iter().cloned().collect();
+    let (mut files, mut num_failed) = find_files(&files, config.show_hidden)?;
+    sort_paths(&mut files, config.sort_by, config.reverse);
+    let mut need_blank = !files.is_empty();
+    print_paths(&files, &config, false)?;
+
+    let mut dirs: Vec<PathBuf> = dirs.into_iter().map(PathBuf::from).collect();
+    sort_paths(&mut dirs, config.sort_by, config.reverse);
+    for dir in dirs {
+        num_failed += list_recursive(&dir, &config, &mut need_blank)?;
+    }
+    Ok(num_failed)
+}
+
+/// Print paths as a long listing or one name per line. A long listing of
+/// a directory's contents starts with the total space they take up.
+fn print_paths(paths: &[PathBuf], config: &Config, with_total: bool) -> MyResult<()> {
     if config.long {
-        println!("{}", format_output(&paths)?);
+        if with_total {
+            let total = total_blocks(paths);
+            if config.human_readable {
+                println!("total {}", format_size(total * 1024, true));
+            } else {
+                println!("total {}", total);
+            }
+        }
+        // The table ends each row, including the last, with a newline
+        if !paths.is_empty() {
+            print!(
+                "{}",
+                format_output(
+                    paths,
+                    config.quoting_style,
+                    config.indicator_style,
+                    config.human_readable,
+                    config.color
+                )?
+            );
+        }
+    } else if !config.one_per_line && io::stdout().is_terminal() {
+        let width = terminal_size().map_or(80, |(Width(w), _)| w as usize);
+        print!(
+            "{}",
+            format_columns(
+                paths,
+                width,
+                config.quoting_style,
+                config.indicator_style,
+                config.color
+            )
+        );
     } else {
         for path in paths {
-            println!("{}", path.display());
+            let name = display_name(path, config.quoting_style, config.indicator_style);
+            println!("{}", colorize_path(path, &name, config.color));
         }
     }
     Ok(())
 }
 
-fn find_files(paths: &[String], show_hidden: bool) -> MyResult<Vec<PathBuf>> {
+/// Pack entry names into as many columns as fit in `width`, filling each
+/// column top to bottom and separating them by two spaces as ls does
+fn format_columns(
+    paths: &[PathBuf],
+    width: usize,
+    quoting_style: QuotingStyle,
+    indicator_style: IndicatorStyle,
+    color: ColorMode,
+) -> String {
+    let names: Vec<_> = paths
+        .iter()
+        .map(|path| display_name(path, quoting_style, indicator_style))
+        .collect();
+    let lens: Vec<_> = names.iter().map(|name| name.chars().count()).collect();
+
+    // Try the fewest rows first; one row per name always works
+    let mut layout = (names.len(), vec![lens.iter().copied().max().unwrap_or(0)]);
+    for rows in 1..names.len() {
+        let widths: Vec<_> = lens
+            .chunks(rows)
+            .map(|column| column.iter().copied().max().unwrap_or(0))
+            .collect();
+        if widths.iter().sum::<usize>() + 2 * (widths.len() - 1) <= width {
+            layout = (rows, widths);
+            break;
+        }
+    }
+
+    let (rows, widths) = layout;
+    let mut output = String::new();
+    for row in 0..rows {
+        let cells: Vec<_> = (row..names.len()).step_by(rows).collect();
+        for (col, &i) in cells.iter().enumerate() {
+            // Padding goes outside the color codes, which take up no space
+            output.push_str(&colorize_path(&paths[i], &names[i], color));
+            if col + 1 < cells.len() {
+                output.push_str(&" ".repeat(widths[col] - lens[i] + 2));
+            }
+        }
+        output.push('\n');
+    }
+    output
+}
+
+/// Print a directory's sorted entries under a "dir:" header and then each
+/// of its subdirectories in turn, as they are walked, giving the number of
+/// directories that could not be read. Symlinks to directories are listed
+/// but not followed.
+fn list_recursive(dir: &Path, config: &Config, need_blank: &mut bool) -> MyResult<usize> {
+    if *need_blank {
+        println!();
+    }
+    println!("{}:", dir.display());
+    *need_blank = true;
+
+    let (mut entries, mut num_failed) =
+        find_files(&[dir.display().to_string()], config.show_hidden)?;
+    sort_paths(&mut entries, config.sort_by, config.reverse);
+    if num_failed > 0 {
+        return Ok(num_failed);
+    }
+    print_paths(&entries, config, true)?;
+    let subdirs: Vec<_> = entries
+        .iter()
+        .filter(|path| fs::symlink_metadata(path).is_ok_and(|meta| meta.is_dir()))
+        .cloned()
+        .collect();
+
+    for subdir in subdirs {
+        num_failed += list_recursive(&subdir, config, need_blank)?;
+    }
+    Ok(num_failed)
+}
+
+/// Sort paths by name, or by modification time or size, optionally reversed
+fn sort_paths(paths: &mut [PathBuf], sort_by: SortBy, reverse: bool) {
+    match sort_by {
+        SortBy::Name => paths.sort(),
+        SortBy::Time => paths.sort_by_cached_key(|path| {
+            let modified = path.metadata().and_then(|meta| meta.modified()).ok();
+            (Reverse(modified), path.clone())
+        }),
+        SortBy::Size => paths.sort_by_cached_key(|path| {
+            let size = path.metadata().map(|meta| meta.len()).unwrap_or(0);
+            (Reverse(size), path.clone())
+        }),
+    }
+    if reverse {
+        paths.reverse();
+    }
+}
+
+/// List the named files and the contents of the named directories,
+/// reporting each path that cannot be read and counting how many failed
+fn find_files(paths: &[String], show_hidden: bool) -> MyResult<(Vec<PathBuf>, usize)> {
     let mut files = vec![];
+    let mut num_failed = 0;
 
     for path in paths {
         match fs::metadata(path) {
             Ok(metadata) => {
                 if metadata.is_dir() {
-                    for entry in fs::read_dir(path)? {
+                    let entries = match fs::read_dir(path) {
+                        Ok(entries) => entries,
+                        Err(e) => {
+                            eprintln!("lsr: {}: {}", path, error_message(&e));
+                            num_failed += 1;
+                            continue;
+                        }
+                    };
+                    for entry in entries {
                         let entry = entry?;
                         let path = entry.path();
-                        let is_hidden = path.file_name().map_or(false, |file_name| {
-                            file_name.to_string_lossy().starts_with('.')
-                        });
+                        let is_hidden = path
+                            .file_name()
+                            .is_some_and(|file_name| file_name.to_string_lossy().starts_with('.'));
                         if !is_hidden || show_hidden {
                             files.push(entry.path());
                         }
@@ -84,50 +404,306 @@ fn find_files(paths: &[String], show_hidden: bool) -> MyResult<Vec<PathBuf>> {
                     files.push(PathBuf::from(path));
                 }
             }
-            Err(e) => eprintln!("{}: {}", path, e),
+            Err(e) => {
+                eprintln!("lsr: {}: {}", path, error_message(&e));
+                num_failed += 1;
+            }
         }
     }
-    Ok(files)
+    Ok((files, num_failed))
 }
 
-fn format_output(paths: &[PathBuf]) -> MyResult<String> {
+/// An I/O error as ls words it, without Rust's "(os error N)" suffix
+fn error_message(e: &io::Error) -> String {
+    let message = e.to_string();
+    match message.find(" (os error ") {
+        Some(i) => message[..i].to_string(),
+        None => message,
+    }
+}
+
+fn format_output(
+    paths: &[PathBuf],
+    quoting_style: QuotingStyle,
+    indicator_style: IndicatorStyle,
+    human_readable: bool,
+    color: ColorMode,
+) -> MyResult<String> {
     //         1   2     3     4     5     6     7     8
     let fmt = "{:<}{:<}  {:>}  {:<}  {:<}  {:>}  {:<}  {:<}";
     let mut table = Table::new(fmt);
 
     for path in paths {
-        let metadata = path.metadata()?;
-
-        let uid = metadata.uid();
-        let user = get_user_by_uid(uid)
-            .map(|u| u.name().to_string_lossy().into_owned())
-            .unwrap_or_else(|| uid.to_string());
+        // Links are described themselves rather than what they point to
+        let metadata = path.symlink_metadata()?;
 
-        let gid = metadata.gid();
-        let group = get_group_by_gid(gid)
-            .map(|g| g.name().to_string_lossy().into_owned())
-            .unwrap_or_else(|| gid.to_string());
-
-        let file_type = if path.is_dir() { "d" } else { "-" };
-        let perms = format_mode(metadata.mode());
-        let modified: DateTime<Local> = DateTime::from(metadata.modified()?);
+        let (perms, links, user, group) = long_fields(&metadata);
+        let file_type = if metadata.is_symlink() {
+            "l"
+        } else if metadata.is_dir() {
+            "d"
+        } else {
+            "-"
+        };
+        let mut name = colorize(
+            &display_name(path, quoting_style, indicator_style),
+            &metadata,
+            color,
+        );
+        if metadata.is_symlink() {
+            if let Ok(target) = fs::read_link(path) {
+                name.push_str(&format!(" -> {}", target.display()));
+            }
+        }
 
         table.add_row(
             Row::new()
-                .with_cell(file_type) // 1 "d" or "-"
+                .with_cell(file_type) // 1 "d", "l" or "-"
                 .with_cell(perms) // 2 permissions
-                .with_cell(metadata.nlink()) // 3 number of links
+                .with_cell(links) // 3 number of links
                 .with_cell(user) // 4 user name
                 .with_cell(group) // 5 group name
-                .with_cell(metadata.len()) // 6 size
-                .with_cell(modified.format("%b %d %y %H:%M")) // 7 modification
-                .with_cell(path.display()), // 8 path
+                .with_cell(format_size(
+                    metadata.len(),
+                    human_readable && !metadata.is_dir(),
+                )) // 6 size
+                .with_cell(format_modified(metadata.modified()?)) // 7 modification
+                .with_cell(name), // 8 path
         );
     }
 
     Ok(format!("{}", table))
 }
 
+/// The quoted name of a path followed by its type indicator, if any
+fn display_name(
+    path: &Path,
+    quoting_style: QuotingStyle,
+    indicator_style: IndicatorStyle,
+) -> String {
+    format!(
+        "{}{}",
+        quote_name(&path.display().to_string(), quoting_style),
+        indicator(path, indicator_style)
+    )
+}
+
+/// Color a name like ls does for its file type: blue for directories,
+/// cyan for symlinks and green for executables
+fn colorize(name: &str, metadata: &fs::Metadata, mode: ColorMode) -> String {
+    let enabled = match mode {
+        ColorMode::Auto => io::stdout().is_terminal(),
+        ColorMode::Always => true,
+        ColorMode::Never => false,
+    };
+    let colour = if !enabled {
+        None
+    } else if metadata.is_symlink() {
+        Some(Colour::Cyan)
+    } else if metadata.is_dir() {
+        Some(Colour::Blue)
+    } else if metadata.is_file() && is_executable(metadata) {
+        Some(Colour::Green)
+    } else {
+        None
+    };
+    match colour {
+        Some(colour) => colour.bold().paint(name).to_string(),
+        None => name.to_string(),
+    }
+}
+
+/// [`colorize`] a path's name, leaving it as it is when the path cannot
+/// be read
+fn colorize_path(path: &Path, name: &str, mode: ColorMode) -> String {
+    match path.symlink_metadata() {
+        Ok(metadata) => colorize(name, &metadata, mode),
+        Err(_) => name.to_string(),
+    }
+}
+
+/// The character marking the type of a path under the given [`IndicatorStyle`]:
+/// "/" for directories, and for classify also "@" for symlinks, "|" for
+/// FIFOs, "=" for sockets and "*" for executables
+fn indicator(path: &Path, style: IndicatorStyle) -> &'static str {
+    let metadata = match (style, fs::symlink_metadata(path)) {
+        (IndicatorStyle::None, _) | (_, Err(_)) => return "",
+        (_, Ok(metadata)) => metadata,
+    };
+    let file_type = metadata.file_type();
+    match style {
+        IndicatorStyle::Slash if file_type.is_dir() => "/",
+        IndicatorStyle::Classify if file_type.is_dir() => "/",
+        IndicatorStyle::Classify if file_type.is_symlink() => "@",
+        IndicatorStyle::Classify => special_indicator(&metadata),
+        _ => "",
+    }
+}
+
+/// The classify indicator for FIFOs, sockets and executables
+#[cfg(unix)]
+fn special_indicator(metadata: &fs::Metadata) -> &'static str {
+    let file_type = metadata.file_type();
+    if file_type.is_fifo() {
+        "|"
+    } else if file_type.is_socket() {
+        "="
+    } else if is_executable(metadata) {
+        "*"
+    } else {
+        ""
+    }
+}
+
+#[cfg(not(unix))]
+fn special_indicator(_metadata: &fs::Metadata) -> &'static str {
+    ""
+}
+
+/// Whether any execute bit is set
+#[cfg(unix)]
+fn is_executable(metadata: &fs::Metadata) -> bool {
+    metadata.mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(_metadata: &fs::Metadata) -> bool {
+    false
+}
+
+/// Quote a file name for display according to the given [`QuotingStyle`]
+fn quote_name(name: &str, style: QuotingStyle) -> String {
+    match style {
+        QuotingStyle::Literal => name.to_string(),
+        QuotingStyle::Shell => {
+            let needs_quotes = name.chars().any(|c| {
+                c.is_whitespace() || c.is_control() || "'\"\\$`&;|<>()*?[]{}#~=%!".contains(c)
+            });
+            if needs_quotes {
+                format!("'{}'", name.replace('\'', "'\\''"))
+            } else {
+                name.to_string()
+            }
+        }
+        QuotingStyle::C => {
+            let mut quoted = String::from("\"");
+            for c in name.chars() {
+                match c {
+                    '\\' => quoted.push_str("\\\\"),
+                    '"' => quoted.push_str("\\\""),
+                    '\x07' => quoted.push_str("\\a"),
+                    '\x08' => quoted.push_str("\\b"),
+                    '\x0c' => quoted.push_str("\\f"),
+                    '\n' => quoted.push_str("\\n"),
+                    '\r' => quoted.push_str("\\r"),
+                    '\t' => quoted.push_str("\\t"),
+                    '\x0b' => quoted.push_str("\\v"),
+                    c if c.is_control() => quoted.push_str(&format!("\\{:03o}", c as u32)),
+                    c => quoted.push(c),
+                }
+            }
+            quoted.push('"');
+            quoted
+        }
+    }
+}
+
+/// Format a size in bytes, or when `human` is set in the largest
+/// 1024-based unit with one decimal place below 10, rounding up like ls
+fn format_size(len: u64, human: bool) -> String {
+    const UNITS: [&str; 6] = ["K", "M", "G", "T", "P", "E"];
+    if !human || len < 1024 {
+        return len.to_string();
+    }
+    let mut size = len as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    let tenths = (size * 10.0).ceil();
+    if tenths < 100.0 {
+        format!("{:.1}{}", tenths / 10.0, UNITS[unit])
+    } else {
+        format!("{:.0}{}", size.ceil(), UNITS[unit])
+    }
+}
+
+/// The permissions, link count, user and group of a long listing row
+#[cfg(unix)]
+fn long_fields(metadata: &fs::Metadata) -> (String, u64, String, String) {
+    (
+        format_mode(metadata.mode()),
+        metadata.nlink(),
+        uid_to_name(metadata.uid()),
+        gid_to_name(metadata.gid()),
+    )
+}
+
+/// Without mode bits or owner ids, the permissions only reflect the
+/// read-only attribute and the owner columns show "-"
+#[cfg(not(unix))]
+fn long_fields(metadata: &fs::Metadata) -> (String, u64, String, String) {
+    let mode = if metadata.permissions().readonly() {
+        0o444
+    } else {
+        0o666
+    };
+    (format_mode(mode), 1, "-".to_string(), "-".to_string())
+}
+
+/// The space allocated to the paths in 1K blocks, as in the total line of
+/// ls -l
+fn total_blocks(paths: &[PathBuf]) -> u64 {
+    paths
+        .iter()
+        .filter_map(|path| path.symlink_metadata().ok())
+        .map(|metadata| allocated_kib(&metadata))
+        .sum()
+}
+
+/// The space allocated to a file in 1K blocks; the metadata counts 512-byte
+/// blocks
+#[cfg(unix)]
+fn allocated_kib(metadata: &fs::Metadata) -> u64 {
+    metadata.blocks().div_ceil(2)
+}
+
+/// Without a block count, the size rounded up to whole 1K blocks
+#[cfg(not(unix))]
+fn allocated_kib(metadata: &fs::Metadata) -> u64 {
+    metadata.len().div_ceil(1024)
+}
+
+/// The name of a user, or like ls the number when it has no account
+#[cfg(unix)]
+fn uid_to_name(uid: u32) -> String {
+    get_user_by_uid(uid)
+        .map(|u| u.name().to_string_lossy().into_owned())
+        .unwrap_or_else(|| uid.to_string())
+}
+
+/// The name of a group, or its number when it has none
+#[cfg(unix)]
+fn gid_to_name(gid: u32) -> String {
+    get_group_by_gid(gid)
+        .map(|g| g.name().to_string_lossy().into_owned())
+        .unwrap_or_else(|| gid.to_string())
+}
+
+/// Format a modification time in the local time zone, as ls does
+fn format_modified(modified: SystemTime) -> String {
+    format_time(DateTime::<Local>::from(modified))
+}
+
+/// Format a time in its own zone, with the day padded by a space
+fn format_time<Tz: TimeZone>(time: DateTime<Tz>) -> String
+where
+    Tz::Offset: fmt::Display,
+{
+    time.format("%b %e %y %R").to_string()
+}
+
 /// Given a file mode in octal format like 0o751,
 /// return a string like "rwxr-x--x"
 fn format_mode(mode: u32) -> String {
@@ -153,8 +729,79 @@ pub fn mk_triple(mode: u32, owner: Owner) -> String {
 
 #[cfg(test)]
 mod test {
-    use super::{find_files, format_mode, format_output};
-    use std::path::PathBuf;
+    use super::{
+        colorize, find_files, format_columns, format_mode, format_output, format_size, format_time,
+        indicator, quote_name, ColorMode, IndicatorStyle, QuotingStyle,
+    };
+    #[cfg(unix)]
+    use super::{gid_to_name, uid_to_name};
+    use chrono::{FixedOffset, TimeZone};
+    use std::{fs, path::PathBuf};
+
+    #[test]
+    fn test_format_columns() {
+        let paths: Vec<PathBuf> = ["a", "bb", "ccc", "dddd", "e", "ffffff", "g"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+        let columns = |width| {
+            format_columns(
+                &paths,
+                width,
+                QuotingStyle::Literal,
+                IndicatorStyle::None,
+                ColorMode::Never,
+            )
+        };
+
+        assert_eq!(columns(80), "a  bb  ccc  dddd  e  ffffff  g\n");
+        assert_eq!(columns(20), "a   ccc   e       g\nbb  dddd  ffffff\n");
+        assert_eq!(columns(14), "a    dddd    g\nbb   e\nccc  ffffff\n");
+        // A name wider than the terminal still gets a line of its own
+        assert_eq!(columns(3), "a\nbb\nccc\ndddd\ne\nffffff\ng\n");
+        assert_eq!(
+            format_columns(
+                &[],
+                80,
+                QuotingStyle::Literal,
+                IndicatorStyle::None,
+                ColorMode::Never
+            ),
+            ""
+        );
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(0, true), "0");
+        assert_eq!(format_size(1023, true), "1023");
+        assert_eq!(format_size(1024, true), "1.0K");
+        assert_eq!(format_size(1536, true), "1.5K");
+        assert_eq!(format_size(1025, true), "1.1K");
+        assert_eq!(format_size(23 * 1024 * 1024, true), "23M");
+        assert_eq!(format_size(5 * 1024 * 1024 + 300 * 1024, true), "5.3M");
+        assert_eq!(format_size(3 * 1024 * 1024 * 1024, true), "3.0G");
+        assert_eq!(format_size(5 * 1024 * 1024, false), "5242880");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_owner_names() {
+        assert_eq!(uid_to_name(0), "root");
+        // Ids without an account fall back to the number
+        assert_eq!(uid_to_name(4_000_000_000), "4000000000");
+        assert_eq!(gid_to_name(4_000_000_000), "4000000000");
+    }
+
+    #[test]
+    fn test_format_time() {
+        // 2020-09-13 12:26:40 and 2020-09-05 10:00:00 UTC, nine hours on,
+        // with the day padded by a space as in ls
+        let tokyo = FixedOffset::east_opt(9 * 60 * 60).unwrap();
+        let time = |secs| tokyo.timestamp_opt(secs, 0).unwrap();
+        assert_eq!(format_time(time(1_600_000_000)), "Sep 13 20 21:26");
+        assert_eq!(format_time(time(1_599_300_000)), "Sep  5 20 19:00");
+    }
 
     #[test]
     fn test_find_files() {
@@ -163,6 +810,7 @@ mod test {
         assert!(res.is_ok());
         let mut filenames: Vec<_> = res
             .unwrap()
+            .0
             .iter()
             .map(|entry| entry.display().to_string())
             .collect();
@@ -182,6 +830,7 @@ mod test {
         assert!(res.is_ok());
         let mut filenames: Vec<_> = res
             .unwrap()
+            .0
             .iter()
             .map(|entry| entry.display().to_string())
             .collect();
@@ -202,6 +851,7 @@ mod test {
         assert!(res.is_ok());
         let filenames: Vec<_> = res
             .unwrap()
+            .0
             .iter()
             .map(|entry| entry.display().to_string())
             .collect();
@@ -218,6 +868,7 @@ mod test {
         assert!(res.is_ok());
         let mut filenames: Vec<_> = res
             .unwrap()
+            .0
             .iter()
             .map(|entry| entry.display().to_string())
             .collect();
@@ -228,12 +879,37 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_find_files_missing() {
+        let (files, num_failed) = find_files(
+            &[
+                "tests/inputs/bustle.txt".to_string(),
+                "tests/inputs/missing.txt".to_string(),
+                "tests/inputs/fox.txt".to_string(),
+            ],
+            false,
+        )
+        .unwrap();
+        assert_eq!(
+            files,
+            [
+                PathBuf::from("tests/inputs/bustle.txt"),
+                PathBuf::from("tests/inputs/fox.txt")
+            ]
+        );
+        assert_eq!(num_failed, 1);
+
+        let (_, num_failed) = find_files(&["tests/inputs".to_string()], false).unwrap();
+        assert_eq!(num_failed, 0);
+    }
+
     #[test]
     fn test_find_files_hidden() {
         let res = find_files(&["tests/inputs".to_string()], true);
         assert!(res.is_ok());
         let mut filenames: Vec<_> = res
             .unwrap()
+            .0
             .iter()
             .map(|entry| entry.display().to_string())
             .collect();
@@ -256,6 +932,7 @@ mod test {
         assert_eq!(format_mode(0o421), "r---w---x");
     }
 
+    #[cfg(unix)]
     fn long_match(
         line: &str,
         expected_name: &str,
@@ -263,9 +940,9 @@ mod test {
         expected_size: Option<&str>,
     ) {
         let parts: Vec<_> = line.split_whitespace().collect();
-        assert!(parts.len() > 0 && parts.len() <= 10);
+        assert!(!parts.is_empty() && parts.len() <= 10);
 
-        let perms = parts.get(0).unwrap();
+        let perms = parts.first().unwrap();
         assert_eq!(perms, &expected_perms);
 
         if let Some(size) = expected_size {
@@ -278,11 +955,100 @@ mod test {
     }
 
     #[test]
+    fn test_quote_name() {
+        assert_eq!(quote_name("a b", QuotingStyle::Literal), "a b");
+        assert_eq!(quote_name("a b", QuotingStyle::Shell), "'a b'");
+        assert_eq!(quote_name("plain.txt", QuotingStyle::Shell), "plain.txt");
+        assert_eq!(quote_name("it's", QuotingStyle::Shell), "'it'\\''s'");
+        assert_eq!(quote_name("a b", QuotingStyle::C), "\"a b\"");
+        assert_eq!(quote_name("a\tb\n", QuotingStyle::C), "\"a\\tb\\n\"");
+        assert_eq!(quote_name("a\x01", QuotingStyle::C), "\"a\\001\"");
+    }
+
+    #[test]
+    fn test_indicator() {
+        let dir = PathBuf::from("tests/inputs/dir");
+        let file = PathBuf::from("tests/inputs/bustle.txt");
+        assert_eq!(indicator(&dir, IndicatorStyle::None), "");
+        assert_eq!(indicator(&dir, IndicatorStyle::Slash), "/");
+        assert_eq!(indicator(&dir, IndicatorStyle::Classify), "/");
+        assert_eq!(indicator(&file, IndicatorStyle::Slash), "");
+        assert_eq!(indicator(&file, IndicatorStyle::Classify), "");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_indicator_executable() -> Result<(), Box<dyn std::error::Error>> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let script = std::env::temp_dir().join(format!("lsr-exec-{}", std::process::id()));
+        fs::write(&script, "")?;
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755))?;
+        assert_eq!(indicator(&script, IndicatorStyle::Slash), "");
+        assert_eq!(indicator(&script, IndicatorStyle::Classify), "*");
+        fs::remove_file(&script)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_colorize() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = fs::metadata("tests/inputs/dir")?;
+        let file = fs::metadata("tests/inputs/bustle.txt")?;
+        assert_eq!(
+            colorize("dir", &dir, ColorMode::Always),
+            "\u{1b}[1;34mdir\u{1b}[0m"
+        );
+        assert_eq!(
+            colorize("bustle.txt", &file, ColorMode::Always),
+            "bustle.txt"
+        );
+        assert_eq!(colorize("dir", &dir, ColorMode::Never), "dir");
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_colorize_executable_symlink() -> Result<(), Box<dyn std::error::Error>> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let script = std::env::temp_dir().join(format!("lsr-color-{}", std::process::id()));
+        fs::write(&script, "")?;
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755))?;
+        let link = script.with_extension("link");
+        std::os::unix::fs::symlink(&script, &link)?;
+
+        assert_eq!(
+            colorize("script", &fs::symlink_metadata(&script)?, ColorMode::Always),
+            "\u{1b}[1;32mscript\u{1b}[0m"
+        );
+        // A link is cyan even when it points to an executable
+        assert_eq!(
+            colorize("link", &fs::symlink_metadata(&link)?, ColorMode::Always),
+            "\u{1b}[1;36mlink\u{1b}[0m"
+        );
+        assert_eq!(
+            colorize("script", &fs::symlink_metadata(&script)?, ColorMode::Never),
+            "script"
+        );
+
+        fs::remove_file(&link)?;
+        fs::remove_file(&script)?;
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(unix)]
     fn test_format_output_one() {
         let bustle_path = "tests/inputs/bustle.txt";
         let bustle = PathBuf::from(bustle_path);
 
-        let res = format_output(&[bustle]);
+        let res = format_output(
+            &[bustle],
+            QuotingStyle::Literal,
+            IndicatorStyle::None,
+            false,
+            ColorMode::Never,
+        );
         assert!(res.is_ok());
 
         let out = res.unwrap();
@@ -290,15 +1056,22 @@ mod test {
         assert_eq!(lines.len(), 1);
 
         let line1 = lines.first().unwrap();
-        long_match(&line1, bustle_path, "-rw-r--r--", Some("193"));
+        long_match(line1, bustle_path, "-rw-r--r--", Some("193"));
     }
 
     #[test]
+    #[cfg(unix)]
     fn test_format_output_two() {
-        let res = format_output(&[
-            PathBuf::from("tests/inputs/dir"),
-            PathBuf::from("tests/inputs/empty.txt"),
-        ]);
+        let res = format_output(
+            &[
+                PathBuf::from("tests/inputs/dir"),
+                PathBuf::from("tests/inputs/empty.txt"),
+            ],
+            QuotingStyle::Literal,
+            IndicatorStyle::None,
+            false,
+            ColorMode::Never,
+        );
         assert!(res.is_ok());
 
         let out = res.unwrap();
@@ -308,13 +1081,49 @@ mod test {
 
         let empty_line = lines.remove(0);
         long_match(
-            &empty_line,
+            empty_line,
             "tests/inputs/empty.txt",
             "-rw-r--r--",
             Some("0"),
         );
 
         let dir_line = lines.remove(0);
-        long_match(&dir_line, "tests/inputs/dir", "drwxr-xr-x", None);
+        long_match(dir_line, "tests/inputs/dir", "drwxr-xr-x", None);
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn test_format_output_windows() -> Result<(), Box<dyn std::error::Error>> {
+        let file = std::env::temp_dir().join(format!("lsr-readonly-{}", std::process::id()));
+        fs::write(&file, "hello")?;
+        let mut perms = fs::metadata(&file)?.permissions();
+        perms.set_readonly(true);
+        fs::set_permissions(&file, perms.clone())?;
+
+        let out = format_output(
+            &[file.clone()],
+            QuotingStyle::Literal,
+            IndicatorStyle::None,
+            false,
+            ColorMode::Never,
+        )?;
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 1);
+        let parts: Vec<_> = lines[0].split_whitespace().collect();
+        assert_eq!(parts[..5], ["-r--r--r--", "1", "-", "-", "5"]);
+
+        let dir = format_output(
+            &[PathBuf::from("tests/inputs/dir")],
+            QuotingStyle::Literal,
+            IndicatorStyle::None,
+            false,
+            ColorMode::Never,
+        )?;
+        assert!(dir.starts_with("drw-rw-rw- "));
+
+        perms.set_readonly(false);
+        fs::set_permissions(&file, perms)?;
+        fs::remove_file(&file)?;
+        Ok(())
     }
 }
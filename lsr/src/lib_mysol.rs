@@ -107,19 +107,17 @@ fn format_output(paths: &[PathBuf]) -> MyResult<String> {
                 .with_cell(meta.nlink()) // 3 number of links
                 .with_cell(
                     users::get_user_by_uid(meta.uid())
-                        .unwrap()
-                        .name()
-                        .to_string_lossy(),
+                        .map(|u| u.name().to_string_lossy().into_owned())
+                        .unwrap_or_else(|| meta.uid().to_string()),
                 ) // 4 user name
                 .with_cell(
                     users::get_group_by_gid(meta.gid())
-                        .unwrap()
-                        .name()
-                        .to_string_lossy(),
+                        .map(|g| g.name().to_string_lossy().into_owned())
+                        .unwrap_or_else(|| meta.gid().to_string()),
                 ) // 5 group name
                 .with_cell(meta.len()) // 6 size
                 .with_cell(
-                    chrono::DateTime::<chrono::Utc>::from(meta.modified().unwrap())
+                    chrono::DateTime::<chrono::Local>::from(meta.modified().unwrap())
                         .format("%b %e %y %R"),
                 ) // 7 modification
                 .with_cell(path.display()), // 8 path
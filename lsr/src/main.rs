@@ -1,6 +1,10 @@
 fn main() {
-    if let Err(e) = lsr::get_args().and_then(lsr::run) {
-        eprintln!("{}", e);
-        std::process::exit(1);
+    match lsr::get_args().and_then(lsr::run) {
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        Ok(num_failed) if num_failed > 0 => std::process::exit(1),
+        Ok(_) => {}
     }
 }
@@ -30,12 +30,20 @@ fn gen_bad_file() -> String {
 #[test]
 fn bad_file() -> TestResult {
     let bad = gen_bad_file();
-    let expected = format!("{}: No such file or directory (os error 2)", &bad);
+    let expected = format!("lsr: {}: No such file or directory\n", &bad);
     Command::cargo_bin(PRG)?
         .arg(&bad)
         .assert()
-        .success()
-        .stderr(predicate::str::contains(expected));
+        .failure()
+        .stderr(expected);
+
+    // The paths that exist are still listed
+    Command::cargo_bin(PRG)?
+        .args([BUSTLE, &bad, FOX])
+        .assert()
+        .failure()
+        .stdout(format!("{}\n{}\n", BUSTLE, FOX))
+        .stderr(format!("lsr: {}: No such file or directory\n", bad));
     Ok(())
 }
 
@@ -61,14 +69,15 @@ fn run_short(arg: &str) -> TestResult {
 }
 
 // --------------------------------------------------
+#[cfg(unix)]
 fn run_long(filename: &str, permissions: &str, size: &str) -> TestResult {
     let cmd = Command::cargo_bin(PRG)?
-        .args(&["--long", filename])
+        .args(["--long", filename])
         .assert()
         .success();
     let stdout = String::from_utf8(cmd.get_output().stdout.clone())?;
     let parts: Vec<_> = stdout.split_whitespace().collect();
-    assert_eq!(parts.get(0).unwrap(), &permissions);
+    assert_eq!(parts.first().unwrap(), &permissions);
     assert_eq!(parts.get(4).unwrap(), &size);
     assert_eq!(parts.last().unwrap(), &filename);
     Ok(())
@@ -81,6 +90,7 @@ fn empty() -> TestResult {
 }
 
 #[test]
+#[cfg(unix)]
 fn empty_long() -> TestResult {
     run_long(EMPTY, "-rw-r--r--", "0")
 }
@@ -92,6 +102,7 @@ fn bustle() -> TestResult {
 }
 
 #[test]
+#[cfg(unix)]
 fn bustle_long() -> TestResult {
     run_long(BUSTLE, "-rw-r--r--", "193")
 }
@@ -103,6 +114,7 @@ fn fox() -> TestResult {
 }
 
 #[test]
+#[cfg(unix)]
 fn fox_long() -> TestResult {
     run_long(FOX, "-rw-------", "45")
 }
@@ -114,6 +126,7 @@ fn hidden() -> TestResult {
 }
 
 #[test]
+#[cfg(unix)]
 fn hidden_long() -> TestResult {
     run_long(HIDDEN, "-rw-r--r--", "0")
 }
@@ -125,7 +138,7 @@ fn dir_short(args: &[&str], expected: &[&str]) -> TestResult {
     let lines: Vec<&str> = stdout.split("\n").filter(|s| !s.is_empty()).collect();
     assert_eq!(lines.len(), expected.len());
     for filename in expected {
-        assert!(lines.contains(&filename));
+        assert!(lines.contains(filename));
     }
     Ok(())
 }
@@ -170,21 +183,71 @@ fn dir2_all() -> TestResult {
     )
 }
 
+#[test]
+fn dir1_indicator_slash() -> TestResult {
+    dir_short(
+        &["--indicator-style=slash", "tests/inputs"],
+        &[
+            "tests/inputs/empty.txt",
+            "tests/inputs/bustle.txt",
+            "tests/inputs/fox.txt",
+            "tests/inputs/dir/",
+        ],
+    )
+}
+
+#[test]
+fn dir1_classify() -> TestResult {
+    dir_short(
+        &["-F", "tests/inputs"],
+        &[
+            "tests/inputs/empty.txt",
+            "tests/inputs/bustle.txt",
+            "tests/inputs/fox.txt",
+            "tests/inputs/dir/",
+        ],
+    )
+}
+
+#[test]
+#[cfg(unix)]
+fn dir1_long_classify() -> TestResult {
+    dir_long(
+        &["-lF", "tests/inputs"],
+        &[
+            ("tests/inputs/empty.txt", "-rw-r--r--", "0"),
+            ("tests/inputs/bustle.txt", "-rw-r--r--", "193"),
+            ("tests/inputs/fox.txt", "-rw-------", "45"),
+            ("tests/inputs/dir/", "drwxr-xr-x", ""),
+        ],
+    )
+}
+
 // --------------------------------------------------
+#[cfg(unix)]
 fn dir_long(args: &[&str], expected: &[(&str, &str, &str)]) -> TestResult {
     let cmd = Command::cargo_bin(PRG)?.args(args).assert().success();
     let stdout = String::from_utf8(cmd.get_output().stdout.clone())?;
-    let lines: Vec<&str> = stdout.split("\n").filter(|s| !s.is_empty()).collect();
+    let mut lines: Vec<&str> = stdout.split("\n").filter(|s| !s.is_empty()).collect();
+    // A directory's contents start with the blocks they take up
+    let total = lines.remove(0);
+    assert!(
+        total
+            .strip_prefix("total ")
+            .is_some_and(|blocks| blocks.parse::<u64>().is_ok()),
+        "{}",
+        total
+    );
     assert_eq!(lines.len(), expected.len());
 
     let mut check = vec![];
     for line in lines {
         let parts: Vec<_> = line.split_whitespace().collect();
-        let path = parts.last().unwrap().clone();
-        let permissions = parts.get(0).unwrap().clone();
+        let path = *parts.last().unwrap();
+        let permissions = *parts.first().unwrap();
         let size = match permissions.chars().next() {
             Some('d') => "",
-            _ => parts.get(4).unwrap().clone(),
+            _ => *parts.get(4).unwrap(),
         };
         check.push((path, permissions, size));
     }
@@ -198,6 +261,7 @@ fn dir_long(args: &[&str], expected: &[(&str, &str, &str)]) -> TestResult {
 
 // --------------------------------------------------
 #[test]
+#[cfg(unix)]
 fn dir1_long() -> TestResult {
     dir_long(
         &["-l", "tests/inputs"],
@@ -211,6 +275,7 @@ fn dir1_long() -> TestResult {
 }
 
 #[test]
+#[cfg(unix)]
 fn dir1_long_all() -> TestResult {
     dir_long(
         &["-la", "tests/inputs"],
@@ -225,6 +290,7 @@ fn dir1_long_all() -> TestResult {
 }
 
 #[test]
+#[cfg(unix)]
 fn dir2_long() -> TestResult {
     dir_long(
         &["--long", "tests/inputs/dir"],
@@ -233,6 +299,7 @@ fn dir2_long() -> TestResult {
 }
 
 #[test]
+#[cfg(unix)]
 fn dir2_long_all() -> TestResult {
     dir_long(
         &["tests/inputs/dir", "--long", "--all"],
@@ -242,3 +309,258 @@ fn dir2_long_all() -> TestResult {
         ],
     )
 }
+
+// --------------------------------------------------
+#[test]
+fn quoting_style() -> TestResult {
+    let dir = std::env::temp_dir().join(format!("lsr-quoting-{}", std::process::id()));
+    fs::create_dir_all(&dir)?;
+    let file = dir.join("has space.txt");
+    fs::write(&file, "")?;
+    let name = file.display().to_string();
+
+    for (style, expected) in [
+        ("literal", name.clone()),
+        ("shell", format!("'{}'", name)),
+        ("c", format!("\"{}\"", name)),
+    ] {
+        Command::cargo_bin(PRG)?
+            .args([&format!("--quoting-style={}", style), &name])
+            .assert()
+            .success()
+            .stdout(format!("{}\n", expected));
+
+        let cmd = Command::cargo_bin(PRG)?
+            .args(["-l", "--quoting-style", style, &name])
+            .assert()
+            .success();
+        let stdout = String::from_utf8(cmd.get_output().stdout.clone())?;
+        assert!(stdout.trim_end().ends_with(&expected));
+    }
+
+    fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+#[cfg(unix)]
+fn long_local_time() -> TestResult {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    let file = std::env::temp_dir().join(format!("lsr-mtime-{}", std::process::id()));
+    fs::write(&file, "")?;
+    // 2020-09-13 12:26:40 UTC
+    fs::File::options()
+        .write(true)
+        .open(&file)?
+        .set_modified(UNIX_EPOCH + Duration::from_secs(1_600_000_000))?;
+    let name = file.display().to_string();
+
+    for (tz, expected) in [
+        ("UTC", "Sep 13 20 12:26"),
+        ("Asia/Tokyo", "Sep 13 20 21:26"),
+    ] {
+        let cmd = Command::cargo_bin(PRG)?
+            .env("TZ", tz)
+            .args(["--long", &name])
+            .assert()
+            .success();
+        let stdout = String::from_utf8(cmd.get_output().stdout.clone())?;
+        let parts: Vec<_> = stdout.split_whitespace().collect();
+        assert_eq!(parts[5..9].join(" "), expected);
+    }
+
+    fs::remove_file(&file)?;
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+#[cfg(unix)]
+fn long_symlink() -> TestResult {
+    let dir = std::env::temp_dir().join(format!("lsr-symlink-{}", std::process::id()));
+    fs::create_dir_all(&dir)?;
+    fs::write(dir.join("target.txt"), "hello\n")?;
+    std::os::unix::fs::symlink("target.txt", dir.join("link.txt"))?;
+    std::os::unix::fs::symlink("missing.txt", dir.join("broken.txt"))?;
+    let dir = dir.display().to_string();
+
+    let cmd = Command::cargo_bin(PRG)?
+        .args(["-l", &dir])
+        .assert()
+        .success();
+    let stdout = String::from_utf8(cmd.get_output().stdout.clone())?;
+    let lines: Vec<_> = stdout.lines().skip(1).collect();
+    assert_eq!(lines.len(), 3);
+    for arrow in ["link.txt -> target.txt", "broken.txt -> missing.txt"] {
+        let arrow = format!("{}/{}", dir, arrow);
+        let line = lines.iter().find(|line| line.ends_with(&arrow));
+        assert!(line.is_some_and(|line| line.starts_with("lrwxrwxrwx")));
+    }
+    let target = format!("{}/target.txt", dir);
+    assert!(lines
+        .iter()
+        .any(|line| line.starts_with("-rw") && line.ends_with(&target)));
+
+    fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn long_total() -> TestResult {
+    let dir = std::env::temp_dir().join(format!("lsr-total-{}", std::process::id()));
+    fs::create_dir_all(&dir)?;
+    fs::write(dir.join("big.txt"), vec![b'x'; 10_000])?;
+    fs::write(dir.join("empty.txt"), "")?;
+    let dir = dir.display().to_string();
+
+    // 10,000 bytes take about ten 1K blocks
+    let cmd = Command::cargo_bin(PRG)?
+        .args(["-l", &dir])
+        .assert()
+        .success();
+    let stdout = String::from_utf8(cmd.get_output().stdout.clone())?;
+    let total = stdout.lines().next().unwrap_or_default();
+    let blocks = total.strip_prefix("total ").map(str::parse::<u64>);
+    assert!(
+        matches!(blocks, Some(Ok(n)) if (10..100).contains(&n)),
+        "{}",
+        total
+    );
+    assert_eq!(stdout.lines().count(), 3);
+
+    // Files named on the command line get no total
+    Command::cargo_bin(PRG)?
+        .args(["-l", BUSTLE, FOX])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("total").not());
+
+    fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn long_human_readable() -> TestResult {
+    let file = std::env::temp_dir().join(format!("lsr-human-{}", std::process::id()));
+    fs::write(&file, vec![b'x'; 1536])?;
+    let name = file.display().to_string();
+
+    for flag in ["-h", "--human-readable"] {
+        let cmd = Command::cargo_bin(PRG)?
+            .args(["-l", flag, &name])
+            .assert()
+            .success();
+        let stdout = String::from_utf8(cmd.get_output().stdout.clone())?;
+        let parts: Vec<_> = stdout.split_whitespace().collect();
+        assert_eq!(parts[4], "1.5K");
+    }
+
+    fs::remove_file(&file)?;
+    Ok(())
+}
+
+// --------------------------------------------------
+fn dir_recursive(args: &[&str], expected: &[(&str, &[&str])]) -> TestResult {
+    let cmd = Command::cargo_bin(PRG)?.args(args).assert().success();
+    let stdout = String::from_utf8(cmd.get_output().stdout.clone())?;
+    let blocks: Vec<&str> = stdout.trim_end().split("\n\n").collect();
+    assert_eq!(blocks.len(), expected.len());
+    for (block, (dir, names)) in blocks.iter().zip(expected) {
+        let mut lines: Vec<&str> = block.lines().collect();
+        assert_eq!(lines.remove(0), format!("{}:", dir));
+        lines.sort();
+        let mut names = names.to_vec();
+        names.sort();
+        assert_eq!(lines, names);
+    }
+    Ok(())
+}
+
+#[test]
+fn dir1_recursive() -> TestResult {
+    dir_recursive(
+        &["-R", "tests/inputs"],
+        &[
+            (
+                "tests/inputs",
+                &[
+                    "tests/inputs/empty.txt",
+                    "tests/inputs/bustle.txt",
+                    "tests/inputs/fox.txt",
+                    "tests/inputs/dir",
+                ],
+            ),
+            ("tests/inputs/dir", &["tests/inputs/dir/spiders.txt"]),
+        ],
+    )
+}
+
+#[test]
+fn dir1_recursive_all() -> TestResult {
+    dir_recursive(
+        &["-aR", "tests/inputs"],
+        &[
+            (
+                "tests/inputs",
+                &[
+                    "tests/inputs/empty.txt",
+                    "tests/inputs/bustle.txt",
+                    "tests/inputs/fox.txt",
+                    "tests/inputs/.hidden",
+                    "tests/inputs/dir",
+                ],
+            ),
+            (
+                "tests/inputs/dir",
+                &["tests/inputs/dir/spiders.txt", "tests/inputs/dir/.gitkeep"],
+            ),
+        ],
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn sorted() -> TestResult {
+    use std::time::{Duration, SystemTime};
+
+    let dir = std::env::temp_dir().join(format!("lsr-sort-{}", std::process::id()));
+    fs::create_dir_all(&dir)?;
+    // Name, size and age in minutes
+    let start = SystemTime::now() - Duration::from_secs(3600);
+    for (name, size, age) in [("a.txt", 30, 3), ("b.txt", 10, 1), ("c.txt", 20, 2)] {
+        let path = dir.join(name);
+        fs::write(&path, vec![b'x'; size])?;
+        fs::File::options()
+            .write(true)
+            .open(&path)?
+            .set_modified(start - Duration::from_secs(60 * age))?;
+    }
+    let root = dir.display().to_string();
+
+    for (args, expected) in [
+        (vec![], ["a.txt", "b.txt", "c.txt"]),
+        (vec!["-r"], ["c.txt", "b.txt", "a.txt"]),
+        (vec!["-t"], ["b.txt", "c.txt", "a.txt"]),
+        (vec!["-tr"], ["a.txt", "c.txt", "b.txt"]),
+        (vec!["-S"], ["a.txt", "c.txt", "b.txt"]),
+        (vec!["-S", "-r"], ["b.txt", "c.txt", "a.txt"]),
+    ] {
+        let expected: Vec<String> = expected
+            .iter()
+            .map(|name| format!("{}\n", dir.join(name).display()))
+            .collect();
+        Command::cargo_bin(PRG)?
+            .args(&args)
+            .arg(&root)
+            .assert()
+            .success()
+            .stdout(expected.concat());
+    }
+
+    fs::remove_dir_all(&dir)?;
+    Ok(())
+}
@@ -5,7 +5,9 @@ use regex::Regex;
 use std::{
     error::Error,
     fs::File,
-    io::{BufRead, BufReader, Read, Seek, SeekFrom},
+    io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write},
+    thread,
+    time::Duration,
 };
 
 type MyResult<T> = Result<T, Box<dyn Error>>;
@@ -24,6 +26,19 @@ pub struct Config {
     lines: TakeValue,
     bytes: Option<TakeValue>,
     quiet: bool,
+    retry: bool,
+    follow: bool,
+    sleep_interval: Duration,
+    zero_terminated: bool,
+}
+
+/// A file watched by -f and how much of it has been shown. The file is
+/// `None` until one named with --retry can be opened.
+#[derive(Debug)]
+struct Followed<'a> {
+    name: &'a str,
+    file: Option<File>,
+    offset: u64,
 }
 
 pub fn get_args() -> MyResult<Config> {
@@ -52,7 +67,7 @@ pub fn get_args() -> MyResult<Config> {
                 .short("c")
                 .long("bytes")
                 .conflicts_with("lines")
-                .help("Number of bytes"),
+                .help("Number of bytes (with optional b, K, M, G unit), or +N to start at byte N"),
         )
         .arg(
             Arg::with_name("lines")
@@ -61,7 +76,36 @@ pub fn get_args() -> MyResult<Config> {
                 .short("n")
                 .long("lines")
                 .default_value("10")
-                .help("Number of lines"),
+                .help("Number of lines, or +N to start at line N"),
+        )
+        .arg(
+            Arg::with_name("retry")
+                .takes_value(false)
+                .long("retry")
+                .help("Keep trying to open a file until it is accessible when following"),
+        )
+        .arg(
+            Arg::with_name("follow")
+                .takes_value(false)
+                .short("f")
+                .long("follow")
+                .help("Output appended data as the files grow"),
+        )
+        .arg(
+            Arg::with_name("sleep_interval")
+                .value_name("SECS")
+                .takes_value(true)
+                .short("s")
+                .long("sleep-interval")
+                .default_value("1.0")
+                .help("With -f, wait SECS seconds between checks for new data"),
+        )
+        .arg(
+            Arg::with_name("zero_terminated")
+                .takes_value(false)
+                .short("z")
+                .long("zero-terminated")
+                .help("Line delimiter is NUL, not newline"),
         )
         .get_matches();
 
@@ -77,107 +121,288 @@ pub fn get_args() -> MyResult<Config> {
         .map(parse_num)
         .unwrap()
         .map_err(|e| format!("illegal line count -- {}", e))?;
+    let sleep_interval = matches
+        .value_of("sleep_interval")
+        .map(parse_interval)
+        .unwrap()
+        .map_err(|e| format!("invalid number of seconds -- {}", e))?;
 
     Ok(Config {
         files,
         lines,
         bytes,
         quiet,
+        retry: matches.is_present("retry"),
+        follow: matches.is_present("follow"),
+        sleep_interval,
+        zero_terminated: matches.is_present("zero_terminated"),
     })
 }
 
+/// Parse a positive number of seconds, which may have a fraction
+fn parse_interval(val: &str) -> MyResult<Duration> {
+    match val.parse::<f64>() {
+        Ok(secs) if secs > 0.0 => Duration::try_from_secs_f64(secs).map_err(|_| From::from(val)),
+        _ => Err(From::from(val)),
+    }
+}
+
 fn parse_num(val: &str) -> MyResult<TakeValue> {
-    let num_re = NUM_RE.get_or_init(|| Regex::new(r"^([+-])?(\d+)$").unwrap());
+    let num_re = NUM_RE.get_or_init(|| Regex::new(r"^([+-])?(\d+)([bkKmMgG])?$").unwrap());
 
     match num_re.captures(val) {
         Some(caps) => {
             let sign = caps.get(1).map_or("-", |m| m.as_str());
             let num = format!("{}{}", sign, caps.get(2).unwrap().as_str());
-            if let Ok(val) = num.parse() {
-                if sign == "+" && val == 0 {
-                    Ok(PlusZero)
-                } else {
-                    Ok(TakeNum(val))
-                }
-            } else {
-                Err(From::from(val))
+            let multiplier: i64 = match caps.get(3).map(|m| m.as_str()) {
+                Some("b") => 512,
+                Some("k" | "K") => 1024,
+                Some("m" | "M") => 1024 * 1024,
+                Some("g" | "G") => 1024 * 1024 * 1024,
+                _ => 1,
+            };
+            match num
+                .parse::<i64>()
+                .ok()
+                .and_then(|n| n.checked_mul(multiplier))
+            {
+                Some(0) if sign == "+" => Ok(PlusZero),
+                Some(val) => Ok(TakeNum(val)),
+                None => Err(From::from(val)),
             }
         }
         _ => Err(From::from(val)),
     }
 }
 
-pub fn run(config: Config) -> MyResult<()> {
-    let num_files = config.files.len();
-
-    for (file_num, filename) in config.files.iter().enumerate() {
-        match File::open(filename) {
-            Err(err) => eprintln!("{}: {}", filename, err),
-            Ok(file) => {
-                if !config.quiet && num_files > 1 {
-                    println!(
-                        "{}==> {} <==",
-                        if file_num > 0 { "\n" } else { "" },
-                        filename
-                    );
+/// Print the tail of each file, giving the number that could not be read.
+/// The readable files are still shown, but the exit status should say
+/// that not all of them were.
+pub fn run(config: Config) -> MyResult<usize> {
+    let headers = !config.quiet && config.files.len() > 1;
+    let mut num_failed = 0;
+    let mut followed = vec![];
+    // The last byte written, so a header never runs into an unfinished line
+    let mut last_byte = None;
+    if config.retry && !config.follow {
+        eprintln!("tailr: warning: --retry ignored; --retry is useful only when following");
+    }
+
+    for filename in &config.files {
+        // With --retry a missing file is waited for while following the
+        // others, rather than holding them up
+        if config.retry && config.follow {
+            if let Err(err) = File::open(filename) {
+                eprintln!(
+                    "tailr: cannot open '{}' for reading: {}; retrying",
+                    filename,
+                    error_message(&err)
+                );
+                followed.push(Followed {
+                    name: filename,
+                    file: None,
+                    offset: 0,
+                });
+                continue;
+            }
+        }
+        match tail_file(filename, &config) {
+            Err(err) => {
+                eprintln!("{}: {}", filename, err);
+                num_failed += 1;
+            }
+            Ok((output, file, offset)) => {
+                if headers && last_byte.is_some() {
+                    println!("{}", newline_if_needed(last_byte));
                 }
-                let (total_lines, total_bytes) = count_lines_bytes(filename)?;
-                let file = BufReader::new(file);
-                if let Some(num_bytes) = &config.bytes {
-                    print_bytes(file, num_bytes, total_bytes)?;
-                } else {
-                    print_lines(file, &config.lines, total_lines)?;
+                print!("{}", output);
+                last_byte = output.bytes().last().or(last_byte);
+                if config.follow {
+                    followed.push(Followed {
+                        name: filename,
+                        file: Some(file),
+                        offset,
+                    });
                 }
             }
         }
     }
-    Ok(())
+    if !followed.is_empty() {
+        follow(followed, headers, last_byte, config.sleep_interval)?;
+    }
+    Ok(num_failed)
+}
+
+/// The tail of one file as it should be printed, starting with its
+/// header when several files are shown, along with the open file and the
+/// offset it was read up to for -f to follow from. The blank line that
+/// separates it from the previous file is left to the caller.
+fn tail_file(filename: &str, config: &Config) -> MyResult<(String, File, u64)> {
+    let delim = if config.zero_terminated { b'\0' } else { b'\n' };
+    let mut file = File::open(filename)?;
+
+    let mut output = Vec::new();
+    if !config.quiet && config.files.len() > 1 {
+        writeln!(output, "==> {} <==", filename)?;
+    }
+    // Only what was counted is shown, so anything appended since is left
+    // for -f rather than shown twice or skipped
+    let (total_lines, total_bytes) = count_lines_bytes(&mut file, delim)?;
+    let offset = total_bytes as u64;
+    if let Some(num_bytes) = &config.bytes {
+        print_bytes(&mut file, &mut output, num_bytes, total_bytes)?;
+    } else {
+        file.rewind()?;
+        let reader = BufReader::new((&mut file).take(offset));
+        print_lines(reader, &mut output, &config.lines, total_lines, delim)?;
+    }
+
+    Ok((String::from_utf8_lossy(&output).into_owned(), file, offset))
+}
+
+/// Print whatever is appended to the files until interrupted, with a
+/// header whenever the output switches to another file. A file that
+/// shrinks is taken to have been truncated and is read from the start,
+/// as is one named with --retry once it appears.
+fn follow(
+    mut files: Vec<Followed>,
+    headers: bool,
+    mut last_byte: Option<u8>,
+    interval: Duration,
+) -> MyResult<()> {
+    let mut stdout = io::stdout();
+    stdout.flush()?;
+    // The initial tails were printed in order, so the last one shown is
+    // current
+    let mut current = files.iter().rposition(|followed| followed.file.is_some());
+    loop {
+        thread::sleep(interval);
+        for (index, followed) in files.iter_mut().enumerate() {
+            if followed.file.is_none() {
+                match File::open(followed.name) {
+                    Ok(file) => {
+                        eprintln!(
+                            "tailr: '{}' has appeared; following new file",
+                            followed.name
+                        );
+                        followed.file = Some(file);
+                    }
+                    Err(_) => continue,
+                }
+            }
+            let Some(file) = &mut followed.file else {
+                continue;
+            };
+            let len = match file.metadata() {
+                Ok(meta) => meta.len(),
+                Err(_) => continue,
+            };
+            if len < followed.offset {
+                eprintln!("tailr: {}: file truncated", followed.name);
+                followed.offset = 0;
+            }
+            if len == followed.offset {
+                continue;
+            }
+
+            file.seek(SeekFrom::Start(followed.offset))?;
+            let mut buffer = Vec::new();
+            file.read_to_end(&mut buffer)?;
+            if buffer.is_empty() {
+                continue;
+            }
+            followed.offset += buffer.len() as u64;
+            if headers && current != Some(index) {
+                if current.is_some() {
+                    writeln!(stdout, "{}", newline_if_needed(last_byte))?;
+                }
+                writeln!(stdout, "==> {} <==", followed.name)?;
+                current = Some(index);
+            }
+            stdout.write_all(&buffer)?;
+            last_byte = buffer.last().copied();
+            stdout.flush()?;
+        }
+    }
+}
+
+/// A newline to finish the output if it stopped partway through a line
+fn newline_if_needed(last_byte: Option<u8>) -> &'static str {
+    match last_byte {
+        Some(b'\n') | None => "",
+        _ => "\n",
+    }
+}
+
+/// An I/O error as tail words it, without Rust's "(os error N)" suffix
+fn error_message(e: &io::Error) -> String {
+    let message = e.to_string();
+    match message.find(" (os error ") {
+        Some(i) => message[..i].to_string(),
+        None => message,
+    }
 }
 
-fn count_lines_bytes(filename: &str) -> MyResult<(i64, i64)> {
-    let mut file = BufReader::new(File::open(filename)?);
+fn count_lines_bytes(file: impl Read, delim: u8) -> MyResult<(i64, i64)> {
+    let mut file = BufReader::new(file);
     let mut num_lines = 0;
     let mut num_bytes = 0;
-    let mut buf = Vec::new();
+    let mut last = None;
     loop {
-        let bytes_read = file.read_until(b'\n', &mut buf)?;
-        if bytes_read == 0 {
+        let buf = file.fill_buf()?;
+        if buf.is_empty() {
             break;
         }
+        num_lines += buf.iter().filter(|&&b| b == delim).count() as i64;
+        num_bytes += buf.len() as i64;
+        last = buf.last().copied();
+        let len = buf.len();
+        file.consume(len);
+    }
+    // A last line without a delimiter still counts
+    if last.is_some_and(|b| b != delim) {
         num_lines += 1;
-        num_bytes += bytes_read as i64;
-        buf.clear();
     }
 
     Ok((num_lines, num_bytes))
 }
 
-fn print_bytes<T>(mut file: T, num_bytes: &TakeValue, total_bytes: i64) -> MyResult<()>
+fn print_bytes<T>(
+    mut file: T,
+    mut out: impl Write,
+    num_bytes: &TakeValue,
+    total_bytes: i64,
+) -> MyResult<()>
 where
     T: Read + Seek,
 {
     if let Some(start) = get_start_index(num_bytes, total_bytes) {
         file.seek(SeekFrom::Start(start))?;
         let mut buffer = Vec::new();
-        file.read_to_end(&mut buffer)?;
-        if !buffer.is_empty() {
-            print!("{}", String::from_utf8_lossy(&buffer));
-        }
+        file.take(total_bytes as u64 - start)
+            .read_to_end(&mut buffer)?;
+        out.write_all(&buffer)?;
     }
     Ok(())
 }
 
-fn print_lines(mut file: impl BufRead, num_lines: &TakeValue, total_lines: i64) -> MyResult<()> {
+fn print_lines(
+    mut file: impl BufRead,
+    mut out: impl Write,
+    num_lines: &TakeValue,
+    total_lines: i64,
+    delim: u8,
+) -> MyResult<()> {
     if let Some(start) = get_start_index(num_lines, total_lines) {
         let mut line_num = 0;
         let mut buf = Vec::new();
         loop {
-            let bytes_read = file.read_until(b'\n', &mut buf)?;
+            let bytes_read = file.read_until(delim, &mut buf)?;
             if bytes_read == 0 {
                 break;
             }
             if line_num >= start {
-                print!("{}", String::from_utf8_lossy(&buf));
+                out.write_all(&buf)?;
             }
             line_num += 1;
             buf.clear();
@@ -209,7 +434,29 @@ fn get_start_index(take_val: &TakeValue, total: i64) -> Option<u64> {
 
 #[cfg(test)]
 mod tests {
-    use super::{count_lines_bytes, get_start_index, parse_num, TakeValue::*};
+    use super::{
+        count_lines_bytes, get_start_index, parse_interval, parse_num, tail_file, Config,
+        TakeValue::*,
+    };
+    use std::{fs::File, time::Duration};
+
+    #[test]
+    fn test_parse_interval() {
+        let res = parse_interval("0.5");
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), Duration::from_millis(500));
+
+        let res = parse_interval("2");
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), Duration::from_secs(2));
+
+        // Anything but a positive number of seconds is an error
+        for bad in ["abc", "0", "-1", "inf", "NaN", ""] {
+            let res = parse_interval(bad);
+            assert!(res.is_err(), "{}", bad);
+            assert_eq!(res.unwrap_err().to_string(), bad);
+        }
+    }
 
     #[test]
     fn test_parse_num() {
@@ -264,16 +511,66 @@ mod tests {
         let res = parse_num("foo");
         assert!(res.is_err());
         assert_eq!(res.unwrap_err().to_string(), "foo");
+
+        // A unit suffix multiplies the number and keeps the sign
+        let res = parse_num("+10k");
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), TakeNum(10 * 1024));
+
+        let res = parse_num("-1M");
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), TakeNum(-1024 * 1024));
+
+        let res = parse_num("2b");
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), TakeNum(-1024));
+
+        let res = parse_num("1G");
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), TakeNum(-1024 * 1024 * 1024));
+
+        let res = parse_num("+0K");
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), PlusZero);
+
+        // An unknown unit is invalid
+        let res = parse_num("10Q");
+        assert!(res.is_err());
+        assert_eq!(res.unwrap_err().to_string(), "10Q");
+
+        // So is a value that overflows once multiplied
+        let res = parse_num(&format!("{}K", i64::MAX));
+        assert!(res.is_err());
     }
     #[test]
     fn test_count_lines_bytes() {
-        let res = count_lines_bytes("tests/inputs/one.txt");
+        let count_lines_bytes =
+            |filename: &str, delim| count_lines_bytes(File::open(filename).unwrap(), delim);
+
+        let res = count_lines_bytes("tests/inputs/one.txt", b'\n');
         assert!(res.is_ok());
         assert_eq!(res.unwrap(), (1, 24));
 
-        let res = count_lines_bytes("tests/inputs/ten.txt");
+        let res = count_lines_bytes("tests/inputs/ten.txt", b'\n');
         assert!(res.is_ok());
         assert_eq!(res.unwrap(), (10, 49));
+
+        let res = count_lines_bytes("tests/inputs/empty.txt", b'\n');
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), (0, 0));
+
+        // The last line counts even without a newline
+        let path = std::env::temp_dir().join(format!("tailr-count-{}", std::process::id()));
+        std::fs::write(&path, "a\n\nb").unwrap();
+        let res = count_lines_bytes(&path.display().to_string(), b'\n');
+        std::fs::remove_file(&path).unwrap();
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), (3, 4));
+
+        // Only NULs delimit lines with -z
+        let res = count_lines_bytes("tests/inputs/nul.txt", b'\0');
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), (4, 25));
     }
 
     #[test]
@@ -299,6 +596,10 @@ mod tests {
         assert_eq!(get_start_index(&TakeNum(1), 10), Some(0));
         assert_eq!(get_start_index(&TakeNum(2), 10), Some(1));
         assert_eq!(get_start_index(&TakeNum(3), 10), Some(2));
+        assert_eq!(get_start_index(&TakeNum(10), 10), Some(9));
+
+        // Starting one past the end prints nothing
+        assert_eq!(get_start_index(&TakeNum(11), 10), None);
 
         // When starting line/byte is negative and less than total,
         // return total - start
@@ -310,4 +611,49 @@ mod tests {
         // return 0 to print the whole file
         assert_eq!(get_start_index(&TakeNum(-20), 10), Some(0));
     }
+
+    #[test]
+    fn test_tail_file() {
+        let config = |files: &[&str], quiet: bool| Config {
+            files: files.iter().map(|f| f.to_string()).collect(),
+            lines: TakeNum(-2),
+            bytes: None,
+            quiet,
+            retry: false,
+            follow: false,
+            sleep_interval: Duration::from_secs(1),
+            zero_terminated: false,
+        };
+
+        // A single file has no header
+        let res = tail_file("tests/inputs/two.txt", &config(&["two.txt"], false));
+        assert!(res.is_ok());
+        let (output, _, offset) = res.unwrap();
+        assert_eq!(output, "Two lines.\nFour words.\n");
+
+        // -f follows from the end of what was read
+        assert_eq!(offset, 23);
+
+        // Several files each start with a header
+        let files = ["tests/inputs/three.txt", "tests/inputs/empty.txt"];
+        let res = tail_file(files[0], &config(&files, false));
+        assert!(res.is_ok());
+        assert_eq!(
+            res.unwrap().0,
+            "==> tests/inputs/three.txt <==\nlines,\r\nfour words.\n"
+        );
+
+        // An empty file still gets its header
+        let res = tail_file(files[1], &config(&files, false));
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap().0, "==> tests/inputs/empty.txt <==\n");
+
+        // Unless headers are suppressed
+        let res = tail_file(files[0], &config(&files, true));
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap().0, "lines,\r\nfour words.\n");
+
+        // A missing file is an error
+        assert!(tail_file("tests/inputs/missing.txt", &config(&files, false)).is_err());
+    }
 }
@@ -5,7 +5,7 @@ use regex::Regex;
 use std::{
     error::Error,
     fs::File,
-    io::{BufRead, Read, Seek},
+    io::{BufRead, Read, Seek, SeekFrom},
 };
 
 type MyResult<T> = Result<T, Box<dyn Error>>;
@@ -135,11 +135,27 @@ pub fn run(config: Config) -> MyResult<()> {
 }
 
 fn count_lines_bytes(filename: &str) -> MyResult<(i64, i64)> {
-    let file = std::io::BufReader::new(File::open(filename)?);
-    let lines = file.lines().count();
-    let file = std::io::BufReader::new(File::open(filename)?);
-    let bytes = file.bytes().count();
-    Ok((lines as i64, bytes as i64))
+    let mut file = std::io::BufReader::new(File::open(filename)?);
+    let mut num_lines = 0;
+    let mut num_bytes = 0;
+    let mut last = None;
+    loop {
+        let buf = file.fill_buf()?;
+        if buf.is_empty() {
+            break;
+        }
+        num_lines += buf.iter().filter(|&&b| b == b'\n').count() as i64;
+        num_bytes += buf.len() as i64;
+        last = buf.last().copied();
+        let len = buf.len();
+        file.consume(len);
+    }
+    // A last line without a newline still counts
+    if last.is_some_and(|b| b != b'\n') {
+        num_lines += 1;
+    }
+
+    Ok((num_lines, num_bytes))
 }
 
 fn print_bytes<T>(mut file: T, num_bytes: &TakeValue, total_bytes: i64) -> MyResult<()>
@@ -147,11 +163,9 @@ where
     T: Read + Seek,
 {
     if let Some(n) = get_start_index(num_bytes, total_bytes) {
-        let buffer: Vec<u8> = file
-            .bytes()
-            .skip(n as usize)
-            .filter_map(Result::ok)
-            .collect();
+        file.seek(SeekFrom::Start(n))?;
+        let mut buffer = Vec::new();
+        file.read_to_end(&mut buffer)?;
         print!("{}", String::from_utf8_lossy(&buffer));
     }
     Ok(())
@@ -1,6 +1,10 @@
 fn main() {
-    if let Err(e) = tailr::get_args().and_then(tailr::run) {
-        eprintln!("{}", e);
-        std::process::exit(1);
+    match tailr::get_args().and_then(tailr::run) {
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        Ok(num_failed) if num_failed > 0 => std::process::exit(1),
+        Ok(_) => {}
     }
 }
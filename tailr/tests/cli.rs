@@ -2,7 +2,9 @@ use assert_cmd::Command;
 use predicates::prelude::*;
 use rand::{distributions::Alphanumeric, Rng};
 use std::fs::{self, File};
-use std::io::Read;
+use std::io::{Read, Write};
+use std::process::Stdio;
+use std::{thread, time::Duration};
 
 type TestResult = Result<(), Box<dyn std::error::Error>>;
 
@@ -12,6 +14,8 @@ const ONE: &str = "tests/inputs/one.txt";
 const TWO: &str = "tests/inputs/two.txt";
 const THREE: &str = "tests/inputs/three.txt";
 const TEN: &str = "tests/inputs/ten.txt";
+const NUL: &str = "tests/inputs/nul.txt";
+const NO_NEWLINE: &str = "tests/inputs/no_newline.txt";
 
 // --------------------------------------------------
 fn random_string() -> String {
@@ -49,7 +53,7 @@ fn dies_bad_bytes() -> TestResult {
     let bad = random_string();
     let expected = format!("illegal byte count -- {}", &bad);
     Command::cargo_bin(PRG)?
-        .args(&["-c", &bad, EMPTY])
+        .args(["-c", &bad, EMPTY])
         .assert()
         .failure()
         .stderr(predicate::str::contains(expected));
@@ -63,7 +67,7 @@ fn dies_bad_lines() -> TestResult {
     let bad = random_string();
     let expected = format!("illegal line count -- {}", &bad);
     Command::cargo_bin(PRG)?
-        .args(&["-n", &bad, EMPTY])
+        .args(["-n", &bad, EMPTY])
         .assert()
         .failure()
         .stderr(predicate::str::contains(expected));
@@ -71,6 +75,21 @@ fn dies_bad_lines() -> TestResult {
     Ok(())
 }
 
+// --------------------------------------------------
+#[test]
+fn dies_bad_sleep_interval() -> TestResult {
+    for bad in [random_string(), "0".to_string()] {
+        let expected = format!("invalid number of seconds -- {}", &bad);
+        Command::cargo_bin(PRG)?
+            .args(["-s", &bad, EMPTY])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(expected));
+    }
+
+    Ok(())
+}
+
 // --------------------------------------------------
 #[test]
 fn dies_bytes_and_lines() -> TestResult {
@@ -78,7 +97,7 @@ fn dies_bytes_and_lines() -> TestResult {
                used with '--bytes <BYTES>'";
 
     Command::cargo_bin(PRG)?
-        .args(&["-n", "1", "-c", "2"])
+        .args(["-n", "1", "-c", "2"])
         .assert()
         .failure()
         .stderr(predicate::str::contains(msg));
@@ -92,13 +111,29 @@ fn skips_bad_file() -> TestResult {
     let bad = gen_bad_file();
     let expected = format!("{}: .* [(]os error 2[)]", bad);
     Command::cargo_bin(PRG)?
-        .args(&[ONE, &bad, TWO])
+        .args([ONE, &bad, TWO])
         .assert()
         .stderr(predicate::str::is_match(expected)?);
 
     Ok(())
 }
 
+// --------------------------------------------------
+#[test]
+fn fails_bad_file_after_good_ones() -> TestResult {
+    let bad = gen_bad_file();
+    Command::cargo_bin(PRG)?
+        .args(["-n", "1", ONE, &bad, TWO])
+        .assert()
+        .failure()
+        .stdout(format!(
+            "==> {} <==\nÖne line, four wordś.\n\n==> {} <==\nFour words.\n",
+            ONE, TWO
+        ))
+        .stderr(format!("{}: No such file or directory (os error 2)\n", bad));
+    Ok(())
+}
+
 // --------------------------------------------------
 fn run(args: &[&str], expected_file: &str) -> TestResult {
     // Extra work here due to lossy UTF
@@ -705,6 +740,21 @@ fn ten_n_plus_2() -> TestResult {
     run(&[TEN, "-n", "+2"], "tests/expected/ten.txt.n+2.out")
 }
 
+#[test]
+fn ten_n_plus_3() -> TestResult {
+    run(&[TEN, "-n", "+3"], "tests/expected/ten.txt.n+3.out")
+}
+
+#[test]
+fn ten_n_plus_10() -> TestResult {
+    run(&[TEN, "-n", "+10"], "tests/expected/ten.txt.n+10.out")
+}
+
+#[test]
+fn ten_n_plus_11() -> TestResult {
+    run(&[TEN, "-n", "+11"], "tests/expected/ten.txt.n+11.out")
+}
+
 #[test]
 fn ten_c_plus_0() -> TestResult {
     run(&[TEN, "-c", "+0"], "tests/expected/ten.txt.c+0.out")
@@ -720,12 +770,41 @@ fn ten_c_plus_2() -> TestResult {
     run(&[TEN, "-c", "+2"], "tests/expected/ten.txt.c+2.out")
 }
 
+#[test]
+fn ten_c_plus_5() -> TestResult {
+    run(&[TEN, "-c", "+5"], "tests/expected/ten.txt.c+5.out")
+}
+
+#[test]
+fn ten_c_plus_49() -> TestResult {
+    run(&[TEN, "-c", "+49"], "tests/expected/ten.txt.c+49.out")
+}
+
+#[test]
+fn ten_c_plus_50() -> TestResult {
+    run(&[TEN, "-c", "+50"], "tests/expected/ten.txt.c+50.out")
+}
+
 // --------------------------------------------------
 #[test]
 fn multiple_files() -> TestResult {
     run(&[TEN, EMPTY, ONE, THREE, TWO], "tests/expected/all.out")
 }
 
+#[test]
+fn multiple_files_no_newline() -> TestResult {
+    // The next header starts on its own line
+    run(&[NO_NEWLINE, ONE], "tests/expected/no_newline.txt.one.out")
+}
+
+#[test]
+fn multiple_files_no_newline_n1() -> TestResult {
+    run(
+        &["-n", "1", ONE, NO_NEWLINE, EMPTY, TWO],
+        "tests/expected/multiple_no_newline.n1.out",
+    )
+}
+
 #[test]
 fn multiple_files_n0() -> TestResult {
     run(
@@ -829,3 +908,157 @@ fn multiple_files_c_plus_3() -> TestResult {
         "tests/expected/all.c+3.out",
     )
 }
+
+// --------------------------------------------------
+#[test]
+fn retry_waits_for_file() -> TestResult {
+    let dir = std::env::temp_dir();
+    let path = dir.join(format!("tailr-retry-{}", random_string()));
+    let name = path.display().to_string();
+    let mut child = std::process::Command::new(assert_cmd::cargo::cargo_bin(PRG))
+        .args(["-f", "--retry", "-s", "0.2", &name, ONE])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    // Rename into place so the file never appears half-written
+    thread::sleep(Duration::from_millis(500));
+    let tmp = dir.join(format!("tailr-retry-{}", random_string()));
+    fs::write(&tmp, "first\nsecond\n")?;
+    fs::rename(&tmp, &path)?;
+    thread::sleep(Duration::from_millis(1000));
+    child.kill()?;
+
+    // The file that exists is shown without waiting for the missing one,
+    // which is then shown from the start
+    let output = child.wait_with_output()?;
+    fs::remove_file(&path)?;
+    assert_eq!(
+        String::from_utf8(output.stdout)?,
+        format!(
+            "==> {} <==\n{}\n==> {} <==\nfirst\nsecond\n",
+            ONE,
+            fs::read_to_string(ONE)?,
+            name
+        )
+    );
+    assert_eq!(
+        String::from_utf8(output.stderr)?,
+        format!(
+            "tailr: cannot open '{0}' for reading: No such file or directory; retrying\n\
+            tailr: '{0}' has appeared; following new file\n",
+            name
+        )
+    );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn retry_ignored_without_follow() -> TestResult {
+    let missing = gen_bad_file();
+    Command::cargo_bin(PRG)?
+        .args(["--retry", &missing])
+        .assert()
+        .failure()
+        .stderr(predicate::str::starts_with(
+            "tailr: warning: --retry ignored; --retry is useful only when following\n",
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn follow_appended_and_truncated() -> TestResult {
+    let dir = std::env::temp_dir();
+    let first = dir.join(format!("tailr-follow-{}", random_string()));
+    let second = dir.join(format!("tailr-follow-{}", random_string()));
+    fs::write(&first, "one\ntwo\n")?;
+    fs::write(&second, "three\n")?;
+    let (first_name, second_name) = (first.display().to_string(), second.display().to_string());
+
+    let mut child = std::process::Command::new(assert_cmd::cargo::cargo_bin(PRG))
+        .args(["-f", "-n", "1", &first_name, &second_name])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    let pause = || thread::sleep(Duration::from_millis(1500));
+    pause();
+    fs::OpenOptions::new()
+        .append(true)
+        .open(&first)?
+        .write_all(b"appended\n")?;
+    pause();
+    fs::write(&second, "new\n")?;
+    pause();
+    child.kill()?;
+
+    let output = child.wait_with_output()?;
+    fs::remove_file(&first)?;
+    fs::remove_file(&second)?;
+    assert_eq!(
+        String::from_utf8(output.stdout)?,
+        format!(
+            "==> {0} <==\ntwo\n\n==> {1} <==\nthree\n\n==> {0} <==\nappended\n\n==> {1} <==\nnew\n",
+            first_name, second_name
+        )
+    );
+    assert_eq!(
+        String::from_utf8(output.stderr)?,
+        format!("tailr: {}: file truncated\n", second_name)
+    );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn bytes_large_file() -> TestResult {
+    let path = std::env::temp_dir().join(format!("tailr-large-{}", random_string()));
+    let contents: String = (0..2000)
+        .map(|i| format!("{} wordś ünïcode\n", i))
+        .collect();
+    fs::write(&path, &contents)?;
+    let name = path.display().to_string();
+
+    // Compare with skipping the bytes one at a time, as print_bytes once did
+    let total = contents.len();
+    for (arg, skip) in [
+        ("5000", total - 5000),
+        ("+7", 6),
+        ("+20001", 20000),
+        ("10K", total - 10240),
+        ("+2k", 2047),
+        ("3b", total - 1536),
+    ] {
+        let expected: Vec<u8> = contents.bytes().skip(skip).collect();
+        Command::cargo_bin(PRG)?
+            .args(["-c", arg, &name])
+            .assert()
+            .success()
+            .stdout(String::from_utf8_lossy(&expected).into_owned());
+    }
+
+    fs::remove_file(&path)?;
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn nul_z_n2() -> TestResult {
+    run(&[NUL, "-z", "-n", "2"], "tests/expected/nul.txt.z.n2.out")
+}
+
+#[test]
+fn nul_zero_terminated_n_plus_2() -> TestResult {
+    run(
+        &[NUL, "--zero-terminated", "-n", "+2"],
+        "tests/expected/nul.txt.z.n+2.out",
+    )
+}
+
+#[test]
+fn nul_n1() -> TestResult {
+    // Without -z the NULs are ordinary bytes
+    run(&[NUL, "-n", "1"], "tests/expected/nul.txt.n1.out")
+}
@@ -1,7 +1,7 @@
 use clap::{App, Arg};
 use std::error::Error;
-use std::fs::File;
-use std::io::{self, BufRead, BufReader};
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read};
 
 type MyResult<T> = Result<T, Box<dyn Error>>;
 
@@ -12,14 +12,53 @@ pub struct Config {
     words: bool,
     bytes: bool,
     chars: bool,
+    max_line_length: bool,
+    delimiter: Option<char>,
+    total: TotalMode,
+    summary_first: bool,
 }
 
-#[derive(Debug, PartialEq)]
+/// When to print the `total` row, as for GNU wc's `--total`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TotalMode {
+    Auto,
+    Always,
+    Only,
+    Never,
+}
+
+#[derive(Debug, Default, PartialEq)]
 pub struct FileInfo {
-    num_lines: usize,
-    num_words: usize,
-    num_bytes: usize,
-    num_chars: usize,
+    pub num_lines: usize,
+    pub num_words: usize,
+    pub num_bytes: usize,
+    pub num_chars: usize,
+    pub max_line_len: usize,
+    pub num_fields: usize,
+    pub min_fields: usize,
+    pub max_fields: usize,
+}
+
+impl FileInfo {
+    /// Combine the counts of two inputs into the counts for both, as for
+    /// the `total` row. Sums are added, maximums kept, and the fewest
+    /// fields per line only considers inputs that have lines.
+    pub fn merge(&self, other: &FileInfo) -> FileInfo {
+        FileInfo {
+            num_lines: self.num_lines + other.num_lines,
+            num_words: self.num_words + other.num_words,
+            num_bytes: self.num_bytes + other.num_bytes,
+            num_chars: self.num_chars + other.num_chars,
+            max_line_len: self.max_line_len.max(other.max_line_len),
+            num_fields: self.num_fields + other.num_fields,
+            min_fields: match (self.num_lines, other.num_lines) {
+                (0, _) => other.min_fields,
+                (_, 0) => self.min_fields,
+                _ => self.min_fields.min(other.min_fields),
+            },
+            max_fields: self.max_fields.max(other.max_fields),
+        }
+    }
 }
 
 pub fn get_args() -> MyResult<Config> {
@@ -34,6 +73,21 @@ pub fn get_args() -> MyResult<Config> {
                 .help("Input file(s)")
                 .default_value("-"),
         )
+        .arg(
+            Arg::with_name("files0_from")
+                .value_name("FILE")
+                .long("files0-from")
+                .takes_value(true)
+                .help("Read NUL-separated input file names from FILE (- for stdin)"),
+        )
+        .arg(
+            Arg::with_name("files_from")
+                .value_name("FILE")
+                .long("files-from")
+                .takes_value(true)
+                .conflicts_with("files0_from")
+                .help("Read NUL- or newline-separated input file names from FILE (- for stdin)"),
+        )
         .arg(
             Arg::with_name("lines")
                 .short("l")
@@ -63,76 +117,245 @@ pub fn get_args() -> MyResult<Config> {
                 .conflicts_with("bytes")
                 .help("Show character count"),
         )
+        .arg(
+            Arg::with_name("max_line_length")
+                .short("L")
+                .long("max-line-length")
+                .takes_value(false)
+                .help("Show length of the longest line"),
+        )
+        .arg(
+            Arg::with_name("fields")
+                .long("fields")
+                .takes_value(false)
+                .help("Show min/max/total delimited fields per line"),
+        )
+        .arg(
+            Arg::with_name("delimiter")
+                .value_name("DELIMITER")
+                .long("delimiter")
+                .requires("fields")
+                .help("Field delimiter [default: tab]"),
+        )
+        .arg(
+            Arg::with_name("total")
+                .value_name("WHEN")
+                .long("total")
+                .takes_value(true)
+                .possible_values(&["auto", "always", "only", "never"])
+                .default_value("auto")
+                .help("When to print a line with total counts"),
+        )
+        .arg(
+            Arg::with_name("summary_first")
+                .long("summary-first")
+                .takes_value(false)
+                .help("Print the total line before the per-file lines"),
+        )
         .get_matches();
 
     let mut lines = matches.is_present("lines");
     let mut words = matches.is_present("words");
     let mut bytes = matches.is_present("bytes");
     let chars = matches.is_present("chars");
+    let max_line_length = matches.is_present("max_line_length");
 
-    if [lines, words, bytes, chars].iter().all(|v| v == &false) {
+    let delimiter = if matches.is_present("fields") {
+        let delim = matches.value_of("delimiter").unwrap_or("\t");
+        let mut delim_chars = delim.chars();
+        match (delim_chars.next(), delim_chars.next()) {
+            (Some(c), None) => Some(c),
+            _ => {
+                return Err(format!("--delimiter \"{}\" must be a single character", delim).into())
+            }
+        }
+    } else {
+        None
+    };
+
+    if [lines, words, bytes, chars, max_line_length]
+        .iter()
+        .all(|v| v == &false)
+    {
         lines = true;
         words = true;
         bytes = true;
     }
 
+    let list = match (
+        matches.value_of("files0_from"),
+        matches.value_of("files_from"),
+    ) {
+        (Some(list), _) => Some(("--files0-from", list, false)),
+        (_, Some(list)) => Some(("--files-from", list, true)),
+        _ => None,
+    };
+    let files = match list {
+        Some((flag, _, _)) if matches.occurrences_of("files") > 0 => {
+            return Err(format!("file operands cannot be combined with {}", flag).into())
+        }
+        Some((_, list, detect)) => read_file_list(list, detect)?,
+        None => matches.values_of_lossy("files").unwrap(),
+    };
+
     Ok(Config {
-        files: matches.values_of_lossy("files").unwrap(),
+        files,
         lines,
         words,
         bytes,
         chars,
+        max_line_length,
+        delimiter,
+        total: match matches.value_of("total") {
+            Some("always") => TotalMode::Always,
+            Some("only") => TotalMode::Only,
+            Some("never") => TotalMode::Never,
+            _ => TotalMode::Auto,
+        },
+        summary_first: matches.is_present("summary_first"),
     })
 }
 
 pub fn run(config: Config) -> MyResult<()> {
-    let mut total_lines = 0;
-    let mut total_words = 0;
-    let mut total_bytes = 0;
-    let mut total_chars = 0;
+    let mut total = FileInfo::default();
+    let mut infos = vec![];
+    let fields = config.delimiter.is_some();
 
+    // Every count is needed before any row to know how wide the columns are
     for filename in &config.files {
         match open(filename) {
             Err(err) => eprintln!("{}: {}", filename, err),
             Ok(file) => {
-                let fileinfo = count(file)?;
-                println!(
-                    "{}{}{}{}{}",
-                    format_field(fileinfo.num_lines, config.lines),
-                    format_field(fileinfo.num_words, config.words),
-                    format_field(fileinfo.num_bytes, config.bytes),
-                    format_field(fileinfo.num_chars, config.chars),
-                    if filename == "-" {
-                        "".to_string()
-                    } else {
-                        format!(" {}", filename)
-                    }
-                );
-
-                total_lines += fileinfo.num_lines;
-                total_words += fileinfo.num_words;
-                total_bytes += fileinfo.num_bytes;
-                total_chars += fileinfo.num_chars;
+                // Only lines and bytes can be counted without decoding text
+                let fileinfo = if config.words || config.chars || config.max_line_length || fields {
+                    count(file, config.delimiter)?
+                } else {
+                    count_lines_bytes(file)?
+                };
+                total = total.merge(&fileinfo);
+                let label = if filename == "-" {
+                    None
+                } else {
+                    Some(filename.as_str())
+                };
+                infos.push((fileinfo, label));
             }
         }
     }
 
-    if config.files.len() > 1 {
-        println!(
-            "{}{}{}{} total",
-            format_field(total_lines, config.lines),
-            format_field(total_words, config.words),
-            format_field(total_bytes, config.bytes),
-            format_field(total_chars, config.chars),
-        );
+    let show_total = match config.total {
+        TotalMode::Auto => config.files.len() > 1,
+        TotalMode::Always | TotalMode::Only => true,
+        TotalMode::Never => false,
+    };
+    let mut rows = vec![];
+    if config.total != TotalMode::Only {
+        rows.extend(infos.iter().map(|(info, label)| (info, *label)));
+    }
+    if show_total {
+        let total_row = (&total, Some("total"));
+        if config.summary_first {
+            rows.insert(0, total_row);
+        } else {
+            rows.push(total_row);
+        }
+    }
+
+    let widths = column_widths(rows.iter().map(|(info, _)| *info));
+    for (info, label) in rows {
+        println!("{}", format_row(info, &config, &widths, label));
     }
 
     Ok(())
 }
 
-fn format_field(value: usize, show: bool) -> String {
+/// Read the names of the files to count from `list`, separated by NULs,
+/// or with `detect` by newlines unless the list contains a NUL. Empty
+/// names are skipped.
+fn read_file_list(list: &str, detect: bool) -> MyResult<Vec<String>> {
+    let mut contents = vec![];
+    open(list)
+        .and_then(|mut file| Ok(file.read_to_end(&mut contents)?))
+        .map_err(|e| format!("{}: {}", list, e))?;
+    let separator = if !detect || contents.contains(&0) {
+        b'\0'
+    } else {
+        b'\n'
+    };
+    Ok(contents
+        .split(|&b| b == separator)
+        .map(|name| match separator {
+            b'\n' => name.strip_suffix(b"\r").unwrap_or(name),
+            _ => name,
+        })
+        .filter(|name| !name.is_empty())
+        .map(|name| String::from_utf8_lossy(name).into_owned())
+        .collect())
+}
+
+/// Every count a row can show, in column order
+fn counts(info: &FileInfo) -> [usize; 8] {
+    [
+        info.num_lines,
+        info.num_words,
+        info.num_bytes,
+        info.num_chars,
+        info.max_line_len,
+        info.min_fields,
+        info.max_fields,
+        info.num_fields,
+    ]
+}
+
+/// Which of the [`counts`] are shown
+fn selected(config: &Config) -> [bool; 8] {
+    let fields = config.delimiter.is_some();
+    [
+        config.lines,
+        config.words,
+        config.bytes,
+        config.chars,
+        config.max_line_length,
+        fields,
+        fields,
+        fields,
+    ]
+}
+
+/// The width of each column across the given rows: 8 as before, or wider
+/// when a count needs it, always leaving a space before the value
+fn column_widths<'a>(infos: impl IntoIterator<Item = &'a FileInfo>) -> [usize; 8] {
+    let mut widths = [8; 8];
+    for info in infos {
+        for (width, value) in widths.iter_mut().zip(counts(info)) {
+            *width = (*width).max(value.to_string().len() + 1);
+        }
+    }
+    widths
+}
+
+/// Format the selected counts for one row, followed by its label if any
+fn format_row(
+    info: &FileInfo,
+    config: &Config,
+    widths: &[usize; 8],
+    label: Option<&str>,
+) -> String {
+    let mut row: String = counts(info)
+        .into_iter()
+        .zip(selected(config))
+        .zip(widths)
+        .map(|((value, show), &width)| format_field(value, show, width))
+        .collect();
+    if let Some(label) = label {
+        row += &format!(" {}", label);
+    }
+    row
+}
+
+fn format_field(value: usize, show: bool, width: usize) -> String {
     if show {
-        format!("{:>8}", value)
+        format!("{:>width$}", value, width = width)
     } else {
         "".to_string()
     }
@@ -141,27 +364,51 @@ fn format_field(value: usize, show: bool) -> String {
 fn open(filename: &str) -> MyResult<Box<dyn BufRead>> {
     match filename {
         "-" => Ok(Box::new(BufReader::new(io::stdin()))),
+        // Opening a directory can succeed, leaving an obscure read error
+        _ if fs::metadata(filename).is_ok_and(|meta| meta.is_dir()) => {
+            Err(From::from("Is a directory"))
+        }
         _ => Ok(Box::new(BufReader::new(File::open(filename)?))),
     }
 }
 
-pub fn count(mut file: impl BufRead) -> MyResult<FileInfo> {
+/// Count lines, words, bytes and characters, find the length in characters
+/// of the longest line, and when given a delimiter also count the fields on
+/// each line (an empty line has no fields)
+pub fn count(mut file: impl BufRead, delimiter: Option<char>) -> MyResult<FileInfo> {
     let mut num_lines = 0;
     let mut num_words = 0;
     let mut num_bytes = 0;
     let mut num_chars = 0;
-    let mut line = String::new();
+    let mut max_line_len = 0;
+    let mut num_fields = 0;
+    let mut min_fields: Option<usize> = None;
+    let mut max_fields = 0;
+    let mut buf = Vec::new();
 
     loop {
-        let line_bytes = file.read_line(&mut line)?;
+        let line_bytes = file.read_until(b'\n', &mut buf)?;
         if line_bytes == 0 {
             break;
         }
         num_bytes += line_bytes;
         num_lines += 1;
+        let line = String::from_utf8_lossy(&buf);
         num_words += line.split_whitespace().count();
         num_chars += line.chars().count();
-        line.clear();
+        let text = line.trim_end_matches(&['\r', '\n'][..]);
+        max_line_len = max_line_len.max(text.chars().count());
+        if let Some(delim) = delimiter {
+            let fields = if text.is_empty() {
+                0
+            } else {
+                text.split(delim).count()
+            };
+            num_fields += fields;
+            min_fields = Some(min_fields.map_or(fields, |m| m.min(fields)));
+            max_fields = max_fields.max(fields);
+        }
+        buf.clear();
     }
 
     Ok(FileInfo {
@@ -169,34 +416,205 @@ pub fn count(mut file: impl BufRead) -> MyResult<FileInfo> {
         num_words,
         num_bytes,
         num_chars,
+        max_line_len,
+        num_fields,
+        min_fields: min_fields.unwrap_or(0),
+        max_fields,
     })
 }
 
+/// Count only lines and bytes, which needs no UTF-8 decoding and reads
+/// the file a buffer at a time, however long its lines
+pub fn count_lines_bytes(mut file: impl BufRead) -> MyResult<FileInfo> {
+    let mut info = FileInfo::default();
+    let mut last = None;
+
+    loop {
+        let buf = file.fill_buf()?;
+        if buf.is_empty() {
+            break;
+        }
+        info.num_lines += buf.iter().filter(|&&b| b == b'\n').count();
+        info.num_bytes += buf.len();
+        last = buf.last().copied();
+        let len = buf.len();
+        file.consume(len);
+    }
+    // A last line without a newline still counts
+    if last.is_some_and(|b| b != b'\n') {
+        info.num_lines += 1;
+    }
+    Ok(info)
+}
+
 #[cfg(test)]
 mod tests {
     use crate::format_field;
 
-    use super::{count, FileInfo};
-    use std::io::Cursor;
+    use super::{column_widths, count, count_lines_bytes, FileInfo};
+    use std::{fs, io::Cursor};
 
     #[test]
     fn test_count() {
         let text = "I don't want the world. I just want your half.\r\n";
-        let info = count(Cursor::new(text));
+        let info = count(Cursor::new(text), None);
         assert!(info.is_ok());
         let expected = FileInfo {
             num_lines: 1,
             num_words: 10,
             num_chars: 48,
             num_bytes: 48,
+            max_line_len: 46,
+            num_fields: 0,
+            min_fields: 0,
+            max_fields: 0,
+        };
+        assert_eq!(info.unwrap(), expected);
+    }
+
+    #[test]
+    fn test_count_bytes_chars() {
+        // Non-ASCII characters take more than one byte, and a CRLF ending
+        // is two of each
+        let text = "héllo\n";
+        let info = count(Cursor::new(text), None).unwrap();
+        assert_eq!(info.num_bytes, 7);
+        assert_eq!(info.num_chars, 6);
+
+        let text = "naïve café\r\n日本語\n";
+        let info = count(Cursor::new(text), None).unwrap();
+        assert_eq!(info.num_lines, 2);
+        assert_eq!(info.num_bytes, 24);
+        assert_eq!(info.num_chars, 16);
+    }
+
+    #[test]
+    fn test_count_words() {
+        // A word is any run of non-whitespace, so symbols and non-ASCII
+        // letters are part of words and Unicode spaces separate them
+        let text = "don't stop — naïve café\u{3000}日本語\n";
+        let info = count(Cursor::new(text), None).unwrap();
+        assert_eq!(info.num_words, 6);
+
+        let text = "  leading and trailing  \n\n\ttabs\there\n";
+        let info = count(Cursor::new(text), None).unwrap();
+        assert_eq!(info.num_words, 5);
+    }
+
+    #[test]
+    fn test_count_max_line_len() {
+        let text = "short\na much longer line\r\n\nnaïve\n";
+        let info = count(Cursor::new(text), None).unwrap();
+        assert_eq!(info.max_line_len, 18);
+
+        // The last line counts even without a newline
+        let text = "ab\nabcdef";
+        let info = count(Cursor::new(text), None).unwrap();
+        assert_eq!(info.max_line_len, 6);
+
+        let info = count(Cursor::new(""), None).unwrap();
+        assert_eq!(info.max_line_len, 0);
+    }
+
+    #[test]
+    fn test_count_lines_bytes() {
+        // The fast path agrees with the full count on every sample input
+        for filename in [
+            "tests/inputs/empty.txt",
+            "tests/inputs/fox.txt",
+            "tests/inputs/atlamal.txt",
+            "tests/inputs/fields.tsv",
+        ] {
+            let contents = fs::read(filename).unwrap();
+            let full = count(Cursor::new(&contents), None).unwrap();
+            let fast = count_lines_bytes(Cursor::new(&contents)).unwrap();
+            let expected = FileInfo {
+                num_lines: full.num_lines,
+                num_bytes: full.num_bytes,
+                ..Default::default()
+            };
+            assert_eq!(fast, expected, "{}", filename);
+        }
+
+        let info = count_lines_bytes(Cursor::new("héllo\r\nno newline")).unwrap();
+        assert_eq!(info.num_lines, 2);
+        assert_eq!(info.num_bytes, 18);
+    }
+
+    #[test]
+    fn test_merge() {
+        let fox = count(
+            Cursor::new("The  quick brown fox\tjumps over\n"),
+            Some('\t'),
+        )
+        .unwrap();
+        let other = count(Cursor::new("a\tb\tc\n\nlonger line here\n"), Some('\t')).unwrap();
+        let empty = count(Cursor::new(""), Some('\t')).unwrap();
+
+        let total = [&fox, &empty, &other]
+            .iter()
+            .fold(FileInfo::default(), |acc, info| acc.merge(info));
+        assert_eq!(
+            total,
+            FileInfo {
+                num_lines: 4,
+                num_words: 12,
+                num_bytes: 56,
+                num_chars: 56,
+                max_line_len: 31,
+                num_fields: 6,
+                min_fields: 0,
+                max_fields: 3,
+            }
+        );
+
+        // An input without lines does not lower the fewest fields
+        assert_eq!(fox.merge(&empty).min_fields, 2);
+        assert_eq!(empty.merge(&fox), fox.merge(&empty));
+    }
+
+    #[test]
+    fn test_count_fields() {
+        let text = "a\tb\tc\r\nd\te\n\nf\tg\th\ti\n";
+        let info = count(Cursor::new(text), Some('\t'));
+        assert!(info.is_ok());
+        let expected = FileInfo {
+            num_lines: 4,
+            num_words: 9,
+            num_chars: 20,
+            num_bytes: 20,
+            max_line_len: 7,
+            num_fields: 9,
+            min_fields: 0,
+            max_fields: 4,
         };
         assert_eq!(info.unwrap(), expected);
     }
 
     #[test]
     fn test_format_field() {
-        assert_eq!(format_field(1, false), "");
-        assert_eq!(format_field(3, true), "       3");
-        assert_eq!(format_field(10, true), "      10");
+        assert_eq!(format_field(1, false, 8), "");
+        assert_eq!(format_field(3, true, 8), "       3");
+        assert_eq!(format_field(10, true, 8), "      10");
+        assert_eq!(format_field(123456789, true, 10), " 123456789");
+    }
+
+    #[test]
+    fn test_column_widths() {
+        assert_eq!(column_widths([]), [8; 8]);
+
+        let small = FileInfo {
+            num_lines: 9999999,
+            ..Default::default()
+        };
+        assert_eq!(column_widths([&small]), [8; 8]);
+
+        // A column widens past 8 digits, the others keep their width
+        let big = FileInfo {
+            num_lines: 1,
+            num_bytes: 123456789,
+            ..Default::default()
+        };
+        assert_eq!(column_widths([&small, &big]), [8, 8, 10, 8, 8, 8, 8, 8]);
     }
 }
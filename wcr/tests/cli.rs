@@ -9,6 +9,7 @@ const PRG: &str = "wcr";
 const EMPTY: &str = "tests/inputs/empty.txt";
 const FOX: &str = "tests/inputs/fox.txt";
 const ATLAMAL: &str = "tests/inputs/atlamal.txt";
+const FIELDS: &str = "tests/inputs/fields.tsv";
 
 // --------------------------------------------------
 fn gen_bad_file() -> String {
@@ -29,7 +30,7 @@ fn gen_bad_file() -> String {
 #[test]
 fn dies_chars_and_bytes() -> TestResult {
     Command::cargo_bin(PRG)?
-        .args(&["-m", "-c"])
+        .args(["-m", "-c"])
         .assert()
         .failure()
         .stderr(predicate::str::contains(
@@ -162,8 +163,7 @@ fn atlamal_bytes_lines() -> TestResult {
 #[test]
 fn atlamal_stdin() -> TestResult {
     let input = fs::read_to_string(ATLAMAL)?;
-    let expected =
-        fs::read_to_string("tests/expected/atlamal.txt.stdin.out")?;
+    let expected = fs::read_to_string("tests/expected/atlamal.txt.stdin.out")?;
     Command::cargo_bin(PRG)?
         .write_stdin(input)
         .assert()
@@ -183,6 +183,12 @@ fn test_all_lines() -> TestResult {
     run(&["-l", EMPTY, FOX, ATLAMAL], "tests/expected/all.l.out")
 }
 
+// --------------------------------------------------
+#[test]
+fn test_all_max_line_length() -> TestResult {
+    run(&["-lL", EMPTY, FOX, ATLAMAL], "tests/expected/all.lL.out")
+}
+
 // --------------------------------------------------
 #[test]
 fn test_all_words() -> TestResult {
@@ -212,3 +218,215 @@ fn test_all_words_lines() -> TestResult {
 fn test_all_bytes_lines() -> TestResult {
     run(&["-cl", EMPTY, FOX, ATLAMAL], "tests/expected/all.cl.out")
 }
+
+// --------------------------------------------------
+#[test]
+fn fields() -> TestResult {
+    run(
+        &["--fields", FIELDS],
+        "tests/expected/fields.tsv.fields.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn fields_comma() -> TestResult {
+    run(
+        &["-l", "--fields", "--delimiter", ",", FIELDS],
+        "tests/expected/fields.tsv.fields.comma.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_delimiter() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--fields", "--delimiter", ",,", FIELDS])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "--delimiter \",,\" must be a single character",
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn files0_from() -> TestResult {
+    let list = std::env::temp_dir().join(format!("wcr-files0-{}", std::process::id()));
+    fs::write(&list, format!("{}\0{}\0\0{}\0", EMPTY, FOX, ATLAMAL))?;
+    let expected = fs::read_to_string("tests/expected/all.out")?;
+
+    Command::cargo_bin(PRG)?
+        .arg(format!("--files0-from={}", list.display()))
+        .assert()
+        .success()
+        .stdout(expected.clone());
+
+    Command::cargo_bin(PRG)?
+        .arg("--files0-from=-")
+        .write_stdin(fs::read(&list)?)
+        .assert()
+        .success()
+        .stdout(expected);
+
+    fs::remove_file(&list)?;
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_files0_from_bad_list() -> TestResult {
+    let bad = gen_bad_file();
+    Command::cargo_bin(PRG)?
+        .args(["--files0-from", &bad])
+        .assert()
+        .failure()
+        .stderr(predicate::str::is_match(format!(
+            "{}: .* [(]os error 2[)]",
+            bad
+        ))?);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_files0_from_and_files() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--files0-from", "-", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "file operands cannot be combined with --files0-from",
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn total_only() -> TestResult {
+    let expected = fs::read_to_string("tests/expected/all.l.out")?;
+    let total = expected.lines().last().unwrap();
+    Command::cargo_bin(PRG)?
+        .args(["-l", "--total=only", EMPTY, FOX, ATLAMAL])
+        .assert()
+        .success()
+        .stdout(format!("{}\n", total));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn total_always_and_never() -> TestResult {
+    let expected = fs::read_to_string("tests/expected/fox.txt.wc.out")?;
+    let row = expected.trim_end().trim_end_matches(FOX);
+    Command::cargo_bin(PRG)?
+        .args(["-wc", "--total=always", FOX])
+        .assert()
+        .success()
+        .stdout(format!("{}{}\n{}total\n", row, FOX, row));
+
+    let expected = fs::read_to_string("tests/expected/all.out")?;
+    let rows: Vec<_> = expected
+        .lines()
+        .filter(|l| !l.ends_with(" total"))
+        .collect();
+    Command::cargo_bin(PRG)?
+        .args(["--total", "never", EMPTY, FOX, ATLAMAL])
+        .assert()
+        .success()
+        .stdout(format!("{}\n", rows.join("\n")));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn summary_first() -> TestResult {
+    let expected = fs::read_to_string("tests/expected/all.out")?;
+    let mut lines: Vec<_> = expected.lines().collect();
+    let total = lines.pop().unwrap();
+    lines.insert(0, total);
+    Command::cargo_bin(PRG)?
+        .args(["--summary-first", EMPTY, FOX, ATLAMAL])
+        .assert()
+        .success()
+        .stdout(format!("{}\n", lines.join("\n")));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn skips_directory() -> TestResult {
+    let expected = fs::read_to_string("tests/expected/fox.txt.out")?;
+    Command::cargo_bin(PRG)?
+        .args(["tests/inputs", FOX])
+        .assert()
+        .success()
+        .stderr("tests/inputs: Is a directory\n")
+        .stdout(predicate::str::starts_with(expected));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn files_from() -> TestResult {
+    let expected = fs::read_to_string("tests/expected/all.out")?;
+    let lists = [
+        ("nul", format!("{}\0{}\0{}\0", EMPTY, FOX, ATLAMAL)),
+        ("newline", format!("{}\n{}\r\n\n{}\n", EMPTY, FOX, ATLAMAL)),
+    ];
+    for (kind, contents) in lists {
+        let list = std::env::temp_dir().join(format!("wcr-files-{}-{}", kind, std::process::id()));
+        fs::write(&list, &contents)?;
+
+        Command::cargo_bin(PRG)?
+            .args(["--files-from", &list.display().to_string()])
+            .assert()
+            .success()
+            .stdout(expected.clone());
+
+        Command::cargo_bin(PRG)?
+            .args(["--files-from", "-"])
+            .write_stdin(contents)
+            .assert()
+            .success()
+            .stdout(expected.clone());
+
+        fs::remove_file(&list)?;
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_files_from_and_files() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args(["--files-from", "-", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "file operands cannot be combined with --files-from",
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn widens_columns() -> TestResult {
+    // A sparse file is quick to make and counts more than 8 digits of bytes
+    let big = std::env::temp_dir().join(format!("wcr-big-{}", std::process::id()));
+    fs::File::create(&big)?.set_len(123_456_789)?;
+    let big_name = big.to_string_lossy();
+    let expected = format!(
+        "       1 123456789 {}\n       1        48 {}\n       2 123456837 total\n",
+        big_name, FOX
+    );
+    Command::cargo_bin(PRG)?
+        .args(["-lc", &big_name, FOX])
+        .assert()
+        .success()
+        .stdout(expected);
+
+    fs::remove_file(&big)?;
+    Ok(())
+}